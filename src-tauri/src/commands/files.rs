@@ -0,0 +1,93 @@
+use crate::commands::sessions::get_session;
+use crate::models::{FileSession, SessionFile, SessionPatch};
+use crate::parsers::session_index::transcript_ids;
+use crate::parsers::transcript_cache::TranscriptCache;
+use crate::parsers::{build_session_patch, extract_session_files, parse_session_jsonl};
+use crate::roots::find_project;
+use std::fs;
+use std::path::Path;
+use tauri::command;
+
+/// The files each transcript referenced: the cross-session index that
+/// [`list_file_sessions`] queries.
+static FILE_INDEX: TranscriptCache<Vec<SessionFile>> = TranscriptCache::new();
+
+/// Lists the files a session read or modified through its tools.
+#[command]
+pub fn list_session_files(
+    project_id: String,
+    session_id: String,
+) -> Result<Vec<SessionFile>, String> {
+    let session = get_session(project_id, session_id, None)?;
+    Ok(extract_session_files(&session.messages))
+}
+
+/// Lists the project's sessions that referenced `file_path`, most recent
+/// first. A relative path matches any absolute path ending with it. Only
+/// transcripts that changed since the last query are parsed.
+#[command]
+pub fn list_file_sessions(
+    project_id: String,
+    file_path: String,
+) -> Result<Vec<FileSession>, String> {
+    let project_path = find_project(&project_id).map_err(|e| e.to_string())?.path;
+
+    if !project_path.exists() {
+        return Err(format!("Project not found: {}", project_id));
+    }
+
+    let wanted = file_path.trim_end_matches('/');
+    let suffix = format!("/{}", wanted.trim_start_matches("./"));
+    let mut matches = Vec::new();
+
+    for session_id in transcript_ids(&project_path).map_err(|e| e.to_string())? {
+        let path = project_path.join(format!("{}.jsonl", session_id));
+        let Ok(files) = FILE_INDEX.get(&path, |path| index_files(&session_id, &project_id, path))
+        else {
+            continue;
+        };
+
+        for file in files {
+            if file.path == wanted || file.path.ends_with(&suffix) {
+                matches.push(FileSession {
+                    session_id: session_id.clone(),
+                    path: file.path,
+                    access: file.access,
+                    last_touched: file.last_touched,
+                });
+            }
+        }
+    }
+
+    matches.sort_by_key(|m| std::cmp::Reverse(m.last_touched));
+    Ok(matches)
+}
+
+fn index_files(
+    session_id: &str,
+    project_id: &str,
+    path: &Path,
+) -> anyhow::Result<Vec<SessionFile>> {
+    let session = parse_session_jsonl(session_id, project_id, path)?;
+    Ok(extract_session_files(&session.messages))
+}
+
+/// Builds a git patch of everything the session's edits changed, optionally
+/// writing it to `output_path` as well. Files with inexact hunks need
+/// `git apply --unidiff-zero`.
+#[command]
+pub fn export_session_patch(
+    project_id: String,
+    session_id: String,
+    output_path: Option<String>,
+) -> Result<SessionPatch, String> {
+    let session = get_session(project_id, session_id, None)?;
+    let patch = build_session_patch(&session);
+
+    if let Some(output_path) = output_path {
+        fs::write(&output_path, &patch.patch)
+            .map_err(|e| format!("Failed to write patch: {}", e))?;
+    }
+
+    Ok(patch)
+}
@@ -1,13 +1,37 @@
+use crate::models::LaunchSpec;
+use crate::parsers::stream_json::StreamSummary;
 use crate::process_manager::{ProcessManager, SessionInfo, OutputLine};
 use tauri::command;
 use tauri::State;
 
+/// Starts Claude Code in the project's working directory. Without a spec a
+/// plain interactive session is started.
 #[command]
 pub async fn spawn_claude_session(
     project_id: String,
+    spec: Option<LaunchSpec>,
     state: State<'_, ProcessManager>,
 ) -> Result<String, String> {
-    state.spawn_session(project_id)
+    state.spawn_session(project_id, spec.unwrap_or_default())
+}
+
+/// Starts a live session on the history of a past one: its transcript is
+/// continued or, with `fork`, branched into a new transcript. The live
+/// session records which session it resumed.
+#[command]
+pub async fn resume_claude_session(
+    project_id: String,
+    session_id: String,
+    fork: Option<bool>,
+    spec: Option<LaunchSpec>,
+    state: State<'_, ProcessManager>,
+) -> Result<String, String> {
+    state.resume_session(
+        project_id,
+        session_id,
+        fork.unwrap_or(false),
+        spec.unwrap_or_default(),
+    )
 }
 
 #[command]
@@ -49,3 +73,13 @@ pub async fn send_input_to_session(
 ) -> Result<(), String> {
     state.write_to_session(&session_id, input)
 }
+
+/// The state, tool calls, cost and latest result of a session started in
+/// stream-json mode.
+#[command]
+pub fn get_session_stream(
+    session_id: String,
+    state: State<'_, ProcessManager>,
+) -> Result<StreamSummary, String> {
+    state.get_session_stream(&session_id)
+}
@@ -0,0 +1,56 @@
+use crate::store::{MetadataStore, ProjectMeta, ProjectMetaUpdate, SessionMeta, SessionMetaUpdate};
+use std::collections::BTreeMap;
+use tauri::command;
+
+#[command]
+pub fn get_project_metadata(project_id: String) -> Result<ProjectMeta, String> {
+    Ok(load_store()?.project(&project_id))
+}
+
+/// Applies a partial update (favorite, alias, tags, notes) and returns the
+/// resulting metadata.
+#[command]
+pub fn update_project_metadata(
+    project_id: String,
+    update: ProjectMetaUpdate,
+) -> Result<ProjectMeta, String> {
+    let mut store = load_store()?;
+    let meta = store.update_project(&project_id, update);
+    save_store(&store)?;
+    Ok(meta)
+}
+
+#[command]
+pub fn get_session_metadata(project_id: String, session_id: String) -> Result<SessionMeta, String> {
+    Ok(load_store()?.session(&project_id, &session_id))
+}
+
+/// Metadata of the project's sessions that have any, keyed by session id.
+#[command]
+pub fn list_session_metadata(project_id: String) -> Result<BTreeMap<String, SessionMeta>, String> {
+    Ok(load_store()?.project_sessions(&project_id))
+}
+
+/// Applies a partial update (title, pinned, tags, notes) and returns the
+/// resulting metadata.
+#[command]
+pub fn update_session_metadata(
+    project_id: String,
+    session_id: String,
+    update: SessionMetaUpdate,
+) -> Result<SessionMeta, String> {
+    let mut store = load_store()?;
+    let meta = store.update_session(&project_id, &session_id, update);
+    save_store(&store)?;
+    Ok(meta)
+}
+
+fn load_store() -> Result<MetadataStore, String> {
+    MetadataStore::load().map_err(|e| format!("Failed to load metadata: {}", e))
+}
+
+fn save_store(store: &MetadataStore) -> Result<(), String> {
+    store
+        .save()
+        .map_err(|e| format!("Failed to save metadata: {}", e))
+}
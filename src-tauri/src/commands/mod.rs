@@ -1,3 +1,8 @@
+pub mod files;
 pub mod live_sessions;
+pub mod metadata;
 pub mod projects;
 pub mod sessions;
+pub mod shell;
+pub mod usage;
+pub mod watcher;
@@ -1,101 +1,174 @@
-use crate::models::Project;
+use crate::config::ctx_dir;
+use crate::models::{Project, RelocationReport};
+use crate::parsers::project_dir::resolve_working_dir;
+use crate::parsers::session_index::transcript_ids;
+use crate::parsers::session_parser::{file_times, session_time_range};
+use crate::relocation::{execute_relocation, plan_relocation};
+use crate::roots::{find_project, project_dirs, ProjectDir};
+use crate::store::{MetadataStore, ProjectMeta, ProjectMetaUpdate};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use tauri::command;
-use walkdir::WalkDir;
-use std::fs;
+use std::path::Path;
 
 #[command]
 pub fn scan_projects() -> Result<Vec<Project>, String> {
-    let projects_dir = dirs::home_dir()
-        .ok_or("Could not determine home directory")?
-        .join(".claude/projects");
+    let dirs = project_dirs().map_err(|e| format!("Failed to resolve Claude roots: {}", e))?;
+    let store = MetadataStore::load().map_err(|e| format!("Failed to load metadata: {}", e))?;
 
-    if !projects_dir.exists() {
-        return Ok(Vec::new());
-    }
+    let mut projects: Vec<Project> = dirs
+        .iter()
+        .map(|dir| build_project(dir, Vec::new(), store.project(&dir.id)))
+        .collect();
 
-    let mut projects = Vec::new();
-
-    for entry in WalkDir::new(&projects_dir)
-        .max_depth(1)
-        .into_iter()
-        .filter_map(|e| e.ok())
-    {
-        if entry.file_type().is_dir() && entry.path() != projects_dir {
-            if let Some(name) = entry.file_name().to_str() {
-                let project = Project {
-                    id: name.to_string(),
-                    name: name.to_string(),
-                    path: entry.path().to_path_buf(),
-                    sessions: Vec::new(),
-                    created_at: chrono::Utc::now().to_rfc3339(),
-                    is_favorite: false,
-                };
-                projects.push(project);
-            }
-        }
-    }
+    // Most recently active first
+    projects.sort_by_key(|p| std::cmp::Reverse(p.updated_at));
 
     Ok(projects)
 }
 
 #[command]
 pub fn get_project(project_id: String) -> Result<Project, String> {
-    let projects_dir = dirs::home_dir()
-        .ok_or("Could not determine home directory")?
-        .join(".claude/projects");
-
-    let project_path = projects_dir.join(&project_id);
+    let dir = find_project(&project_id).map_err(|e| e.to_string())?;
 
-    if !project_path.exists() {
+    if !dir.path.exists() {
         return Err(format!("Project not found: {}", project_id));
     }
 
-    // Try to read sessions from sessions-index.json
-    let index_path = project_path.join("sessions-index.json");
-    let mut sessions = Vec::new();
-
-    if index_path.exists() {
-        if let Ok(content) = std::fs::read_to_string(&index_path) {
-            if let Ok(json) = serde_json::from_str::<serde_json::Value>(&content) {
-                if let Some(sessions_array) = json.get("sessions").and_then(|v| v.as_array()) {
-                    for session in sessions_array {
-                        if let Some(id) = session.get("id").and_then(|v| v.as_str()) {
-                            sessions.push(id.to_string());
-                        }
-                    }
-                }
-            }
-        }
-    }
+    let sessions =
+        transcript_ids(&dir.path).map_err(|e| format!("Failed to list sessions: {}", e))?;
 
-    Ok(Project {
-        id: project_id.clone(),
-        name: project_id,
-        path: project_path,
-        sessions,
-        created_at: chrono::Utc::now().to_rfc3339(),
-        is_favorite: false,
-    })
+    let store = MetadataStore::load().map_err(|e| format!("Failed to load metadata: {}", e))?;
+    Ok(build_project(&dir, sessions, store.project(&project_id)))
 }
 
+/// Gives a project a display name. Only CTX's metadata changes; the
+/// directory under `~/.claude/projects` that Claude Code maps the working
+/// directory to is left alone. A blank name restores the default.
 #[command]
-pub fn rename_project(old_id: String, new_id: String) -> Result<(), String> {
-    let projects_dir = dirs::home_dir()
-        .ok_or("Could not determine home directory".to_string())?
-        .join(".claude/projects");
+pub fn rename_project(project_id: String, name: String) -> Result<Project, String> {
+    let mut store = MetadataStore::load().map_err(|e| format!("Failed to load metadata: {}", e))?;
+    store.update_project(
+        &project_id,
+        ProjectMetaUpdate {
+            alias: Some(name),
+            ..Default::default()
+        },
+    );
+    store
+        .save()
+        .map_err(|e| format!("Failed to save metadata: {}", e))?;
+
+    get_project(project_id)
+}
 
-    let old_path = projects_dir.join(&old_id);
-    let new_path = projects_dir.join(&new_id);
+/// Points a project's Claude Code history at a repository that was moved to
+/// `new_working_dir`: the project directory under `~/.claude/projects` moves
+/// to the name Claude Code derives from the new path, and the `cwd` of every
+/// transcript line is rewritten. CTX's metadata follows.
+///
+/// With `dry_run` only the plan is returned. Otherwise `confirm` must be set;
+/// the project is backed up to `~/.ctx/backups` first and restored from there
+/// if any step fails.
+#[command]
+pub fn relocate_project(
+    project_id: String,
+    new_working_dir: String,
+    dry_run: bool,
+    confirm: bool,
+) -> Result<RelocationReport, String> {
+    let dir = find_project(&project_id).map_err(|e| e.to_string())?;
+
+    let plan = plan_relocation(&dir.root, &dir.dir_name, Path::new(&new_working_dir))
+        .map_err(|e| format!("Failed to plan relocation: {:#}", e))?;
+
+    if dry_run {
+        return Ok(RelocationReport {
+            plan,
+            dry_run: true,
+            backup_dir: None,
+            project: None,
+        });
+    }
+    if !confirm {
+        return Err(format!(
+            "Relocating moves {} to {} inside Claude Code's data; confirm to proceed",
+            plan.project_id, plan.new_project_id
+        ));
+    }
 
-    if !old_path.exists() {
-        return Err(format!("Project not found: {}", old_id));
+    let backup_root = ctx_dir().map_err(|e| e.to_string())?.join("backups");
+    let backup_dir = execute_relocation(&plan, &backup_root)
+        .map_err(|e| format!("Failed to relocate project: {:#}", e))?;
+
+    let moved = MetadataStore::load().and_then(|mut store| {
+        store.move_project(&plan.project_id, &plan.new_project_id);
+        store.save()
+    });
+    if let Err(e) = moved {
+        return Err(format!(
+            "Project relocated, but its CTX metadata was not moved: {} (backup in {})",
+            e,
+            backup_dir.display()
+        ));
     }
 
-    if new_path.exists() {
-        return Err(format!("Project already exists: {}", new_id));
+    let project = get_project(plan.new_project_id.clone())?;
+    Ok(RelocationReport {
+        plan,
+        dry_run: false,
+        backup_dir: Some(backup_dir),
+        project: Some(project),
+    })
+}
+
+/// Spans from the start of the oldest session to the end of the newest one,
+/// falling back to the directory's own times for projects without sessions.
+fn project_time_range(project_path: &Path) -> (DateTime<Utc>, DateTime<Utc>) {
+    let ranges: Vec<_> = transcript_ids(project_path)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|id| session_time_range(&project_path.join(format!("{}.jsonl", id))).ok())
+        .collect();
+
+    let created = ranges.iter().map(|(created, _)| *created).min();
+    let updated = ranges.iter().map(|(_, updated)| *updated).max();
+
+    match (created, updated) {
+        (Some(created), Some(updated)) => (created, updated),
+        _ => file_times(project_path).unwrap_or_else(|_| {
+            let now = Utc::now();
+            (now, now)
+        }),
     }
+}
 
-    fs::rename(&old_path, &new_path)
-        .map_err(|e| format!("Failed to rename project: {}", e))
+/// Assembles a project from its directory and CTX's metadata about it. The
+/// name is the user's alias, else the working directory's folder name, else
+/// the encoded directory name.
+fn build_project(dir: &ProjectDir, sessions: Vec<String>, meta: ProjectMeta) -> Project {
+    let (created_at, updated_at) = project_time_range(&dir.path);
+    let working_dir = resolve_working_dir(&dir.path);
+    let name = meta.alias.clone().unwrap_or_else(|| {
+        working_dir
+            .as_deref()
+            .and_then(|path| path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| dir.dir_name.clone())
+    });
+
+    Project {
+        id: dir.id.clone(),
+        name,
+        root: dir.root.name.clone(),
+        path: dir.path.clone(),
+        working_dir,
+        sessions,
+        created_at,
+        updated_at,
+        is_favorite: meta.favorite,
+        alias: meta.alias,
+        tags: meta.tags,
+        notes: meta.notes,
+    }
 }
@@ -1,50 +1,512 @@
-use crate::models::Session;
-use crate::parsers::parse_session_jsonl;
+use crate::models::{
+    ContentBlock, ConversationTree, Message, MessageContent, MessageHeader, Session, SessionDelta,
+    SessionIndexReport, SessionListItem, SessionPage, SessionValidation, TailCursor, ToolCall,
+};
+use crate::parsers::session_index::{
+    discover_sessions, read_session_index, transcript_ids, DiscoveredSession,
+};
+use crate::parsers::session_parser::read_session_title;
+use crate::parsers::transcript_cache::TranscriptCache;
+use crate::parsers::{
+    build_conversation_tree, extract_tool_calls, parse_session_jsonl, parse_session_jsonl_with,
+    parse_session_tail, ParseOptions,
+};
+use crate::roots::find_project;
+use crate::store::MetadataStore;
 use anyhow::Result;
+use std::sync::Arc;
 use tauri::command;
 
-#[command]
-pub fn list_sessions(project_id: String) -> Result<Vec<String>, String> {
-    let projects_dir = dirs::home_dir()
-        .ok_or("Could not determine home directory")?
-        .join(".claude/projects");
+const DEFAULT_PAGE_SIZE: usize = 100;
+const MAX_PAGE_SIZE: usize = 1000;
+const PREVIEW_CHARS: usize = 200;
+/// Tool results larger than this are cut down in paged listings.
+const TOOL_RESULT_LIMIT: usize = 4 * 1024;
+
+/// Titles read from transcripts for sessions without one in the metadata.
+static TITLES: TranscriptCache<Option<String>> = TranscriptCache::new();
 
-    let project_path = projects_dir.join(&project_id);
-    let index_path = project_path.join("sessions-index.json");
+/// Messages of the sessions being paged through, so turning a page does not
+/// parse the transcript again.
+static PAGED_MESSAGES: TranscriptCache<Arc<Vec<Message>>> = TranscriptCache::with_limit(4);
 
-    if !index_path.exists() {
-        return Ok(Vec::new());
+/// Lists a project's sessions with their display titles, merging the
+/// session index with the transcripts on disk. Pinned sessions come first,
+/// then the most recently modified.
+#[command]
+pub fn list_sessions(project_id: String) -> Result<Vec<SessionListItem>, String> {
+    let project_path = find_project(&project_id)
+        .map_err(|e| e.to_string())?
+        .path;
+    if !project_path.exists() {
+        return Err(format!("Project not found: {}", project_id));
     }
 
-    let content = std::fs::read_to_string(&index_path).map_err(|e| e.to_string())?;
-    let json: serde_json::Value = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    let discovered = discover_sessions(&project_path).map_err(|e| e.to_string())?;
+    let store = MetadataStore::load().map_err(|e| format!("Failed to load metadata: {}", e))?;
 
-    let mut sessions = Vec::new();
-    if let Some(sessions_array) = json.get("sessions").and_then(|v| v.as_array()) {
-        for session in sessions_array {
-            if let Some(id) = session.get("id").and_then(|v| v.as_str()) {
-                sessions.push(id.to_string());
+    let mut sessions: Vec<SessionListItem> = discovered
+        .into_iter()
+        .map(|session| {
+            let meta = store.session(&project_id, &session.id);
+            let title = meta
+                .title
+                .or_else(|| {
+                    let path = project_path.join(format!("{}.jsonl", session.id));
+                    TITLES.get(&path, read_session_title).ok().flatten()
+                })
+                .unwrap_or_else(|| session.id.clone());
+            SessionListItem {
+                id: session.id,
+                title,
+                pinned: meta.pinned,
+                tags: meta.tags,
+                in_index: session.in_index,
+                on_disk: session.on_disk,
+                size_bytes: session.size,
+                modified_at: session.modified,
+                message_count: session.message_count,
             }
-        }
-    }
+        })
+        .collect();
+
+    sessions.sort_by_key(|s| (!s.pinned, std::cmp::Reverse(s.modified_at)));
 
     Ok(sessions)
 }
 
+/// Compares a project's `sessions-index.json` with its transcripts.
+#[command]
+pub fn check_session_index(project_id: String) -> Result<SessionIndexReport, String> {
+    let project_path = find_project(&project_id)
+        .map_err(|e| e.to_string())?
+        .path;
+
+    if !project_path.exists() {
+        return Err(format!("Project not found: {}", project_id));
+    }
+
+    let (index_exists, index_error) = match read_session_index(&project_path) {
+        Ok(index) => (index.is_some(), None),
+        Err(e) => (true, Some(e.to_string())),
+    };
+    let sessions = discover_sessions(&project_path).map_err(|e| e.to_string())?;
+
+    let ids = |keep: &dyn Fn(&DiscoveredSession) -> bool| -> Vec<String> {
+        sessions
+            .iter()
+            .filter(|s| keep(s))
+            .map(|s| s.id.clone())
+            .collect()
+    };
+
+    Ok(SessionIndexReport {
+        project_id,
+        index_exists,
+        index_error,
+        indexed: sessions.iter().filter(|s| s.in_index).count(),
+        on_disk: sessions.iter().filter(|s| s.on_disk).count(),
+        missing_files: ids(&|s| s.in_index && !s.on_disk),
+        unindexed: ids(&|s| s.on_disk && !s.in_index),
+        // Allow for the index rounding or lagging by a second
+        stale: ids(&|s| match (s.index_modified, s.modified) {
+            (Some(indexed), Some(modified)) => modified - indexed > chrono::Duration::seconds(1),
+            _ => false,
+        }),
+    })
+}
+
+/// Loads a whole session. With `strict`, the first malformed line fails the
+/// call instead of being reported in `Session::diagnostics`.
+#[command]
+pub fn get_session(
+    project_id: String,
+    session_id: String,
+    strict: Option<bool>,
+) -> Result<Session, String> {
+    let session_file = find_project(&project_id)
+        .map_err(|e| e.to_string())?
+        .path
+        .join(format!("{}.jsonl", session_id));
+
+    if !session_file.exists() {
+        return Err(format!("Session not found: {}", session_id));
+    }
+
+    let options = ParseOptions {
+        strict: strict.unwrap_or(false),
+    };
+    let mut session = parse_session_jsonl_with(&session_id, &project_id, &session_file, options)
+        .map_err(|e| format!("Failed to parse session: {}", e))?;
+
+    let store = MetadataStore::load().map_err(|e| format!("Failed to load metadata: {}", e))?;
+    if let Some(title) = store.session_title(&project_id, &session_id) {
+        session.name = title.to_string();
+    }
+
+    Ok(session)
+}
+
+/// Assigns a title to a session in CTX's metadata; `None` reverts to the
+/// title derived from the transcript.
+#[command]
+pub fn set_session_title(
+    project_id: String,
+    session_id: String,
+    title: Option<String>,
+) -> Result<(), String> {
+    let mut store = MetadataStore::load().map_err(|e| format!("Failed to load metadata: {}", e))?;
+    store.set_session_title(&project_id, &session_id, title);
+    store
+        .save()
+        .map_err(|e| format!("Failed to save metadata: {}", e))
+}
+
+/// Returns one page of a session's messages, starting at `offset` or right
+/// after the message with id `after`. With `headers_only` the content is
+/// omitted; otherwise large tool results are truncated and their full size
+/// reported. Use `get_message` to fetch a message's full content.
+#[command]
+pub fn get_session_page(
+    project_id: String,
+    session_id: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    after: Option<String>,
+    headers_only: Option<bool>,
+) -> Result<SessionPage, String> {
+    let messages = paged_messages(&project_id, &session_id)?;
+
+    page(
+        session_id,
+        &messages,
+        offset,
+        limit,
+        after,
+        headers_only.unwrap_or(false),
+    )
+}
+
+fn page(
+    session_id: String,
+    all: &[Message],
+    offset: Option<usize>,
+    limit: Option<usize>,
+    after: Option<String>,
+    headers_only: bool,
+) -> Result<SessionPage, String> {
+    let total = all.len();
+
+    let start = match after {
+        Some(cursor) => all
+            .iter()
+            .position(|m| m.id == cursor)
+            .map(|i| i + 1)
+            .ok_or_else(|| format!("Message not found: {}", cursor))?,
+        None => offset.unwrap_or(0).min(total),
+    };
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let end = (start + limit).min(total);
+
+    let next_cursor = if end < total {
+        Some(all[end - 1].id.clone())
+    } else {
+        None
+    };
+    let messages = all[start..end]
+        .iter()
+        .map(|msg| message_header(msg, headers_only))
+        .collect();
+
+    Ok(SessionPage {
+        session_id,
+        total,
+        offset: start,
+        messages,
+        next_cursor,
+    })
+}
+
+/// Returns a single message with its full, untruncated content.
+#[command]
+pub fn get_message(
+    project_id: String,
+    session_id: String,
+    message_id: String,
+) -> Result<Message, String> {
+    paged_messages(&project_id, &session_id)?
+        .iter()
+        .find(|m| m.id == message_id)
+        .cloned()
+        .ok_or_else(|| format!("Message not found: {}", message_id))
+}
+
+/// A session's messages through [`PAGED_MESSAGES`], parsing the transcript
+/// only when it changed since it was last paged.
+fn paged_messages(project_id: &str, session_id: &str) -> Result<Arc<Vec<Message>>, String> {
+    let session_file = find_project(project_id)
+        .map_err(|e| e.to_string())?
+        .path
+        .join(format!("{}.jsonl", session_id));
+
+    if !session_file.exists() {
+        return Err(format!("Session not found: {}", session_id));
+    }
+
+    PAGED_MESSAGES
+        .get(&session_file, |path| {
+            Ok(Arc::new(
+                parse_session_jsonl(session_id, project_id, path)?.messages,
+            ))
+        })
+        .map_err(|e| format!("Failed to parse session: {}", e))
+}
+
+/// Returns the session's main line, abandoned branches and sidechains.
+#[command]
+pub fn get_session_tree(project_id: String, session_id: String) -> Result<ConversationTree, String> {
+    let session = get_session(project_id, session_id, None)?;
+    Ok(build_conversation_tree(&session))
+}
+
+/// Returns the session's tool calls in order, each paired with its result.
 #[command]
-pub fn get_session(project_id: String, session_id: String) -> Result<Session, String> {
-    let projects_dir = dirs::home_dir()
-        .ok_or("Could not determine home directory")?
-        .join(".claude/projects");
+pub fn get_tool_calls(project_id: String, session_id: String) -> Result<Vec<ToolCall>, String> {
+    let session = get_session(project_id, session_id, None)?;
+    Ok(extract_tool_calls(&session.messages))
+}
 
-    let session_file = projects_dir
-        .join(&project_id)
+/// Returns only the messages appended since `cursor` (or the whole session
+/// when no cursor is given), along with the cursor for the next call.
+#[command]
+pub fn get_session_delta(
+    project_id: String,
+    session_id: String,
+    cursor: Option<TailCursor>,
+) -> Result<SessionDelta, String> {
+    let session_file = find_project(&project_id)
+        .map_err(|e| e.to_string())?
+        .path
         .join(format!("{}.jsonl", session_id));
 
     if !session_file.exists() {
         return Err(format!("Session not found: {}", session_id));
     }
 
-    parse_session_jsonl(&session_id, &project_id, &session_file)
+    parse_session_tail(&session_file, &cursor.unwrap_or_default())
         .map_err(|e| format!("Failed to parse session: {}", e))
 }
+
+/// Reports malformed lines in one session, or in every session file of the
+/// project when `session_id` is omitted.
+#[command]
+pub fn validate_session(
+    project_id: String,
+    session_id: Option<String>,
+) -> Result<Vec<SessionValidation>, String> {
+    let project_path = find_project(&project_id)
+        .map_err(|e| e.to_string())?
+        .path;
+
+    if !project_path.exists() {
+        return Err(format!("Project not found: {}", project_id));
+    }
+
+    let session_ids = match session_id {
+        Some(id) => vec![id],
+        None => transcript_ids(&project_path).map_err(|e| e.to_string())?,
+    };
+
+    Ok(session_ids
+        .into_iter()
+        .map(|id| {
+            let path = project_path.join(format!("{}.jsonl", id));
+            match parse_session_jsonl(&id, &project_id, &path) {
+                Ok(session) => SessionValidation {
+                    session_id: id,
+                    message_count: session.messages.len(),
+                    diagnostics: session.diagnostics,
+                    error: None,
+                },
+                Err(e) => SessionValidation {
+                    session_id: id,
+                    message_count: 0,
+                    diagnostics: Vec::new(),
+                    error: Some(e.to_string()),
+                },
+            }
+        })
+        .collect())
+}
+
+fn message_header(msg: &Message, headers_only: bool) -> MessageHeader {
+    let content_size = serde_json::to_vec(&msg.content).map_or(0, |v| v.len());
+    let preview: String = msg.content.text().chars().take(PREVIEW_CHARS).collect();
+
+    MessageHeader {
+        id: msg.id.clone(),
+        role: msg.role.clone(),
+        timestamp: msg.timestamp,
+        uuid: msg.uuid.clone(),
+        parent_uuid: msg.parent_uuid.clone(),
+        is_sidechain: msg.is_sidechain,
+        model: msg.model.clone(),
+        preview,
+        content_size,
+        content: if headers_only {
+            None
+        } else {
+            Some(truncate_tool_results(&msg.content))
+        },
+    }
+}
+
+fn truncate_tool_results(content: &MessageContent) -> MessageContent {
+    let MessageContent::Blocks(blocks) = content else {
+        return content.clone();
+    };
+
+    let blocks = blocks
+        .iter()
+        .map(|block| match block {
+            ContentBlock::ToolResult {
+                tool_use_id,
+                is_error,
+                content,
+                ..
+            } => {
+                let size = serde_json::to_vec(content).map_or(0, |v| v.len());
+                if size <= TOOL_RESULT_LIMIT {
+                    return block.clone();
+                }
+
+                let text = MessageContent::Blocks(content.clone()).text();
+                ContentBlock::ToolResult {
+                    tool_use_id: tool_use_id.clone(),
+                    is_error: *is_error,
+                    content: vec![ContentBlock::Text {
+                        text: truncate_str(&text, TOOL_RESULT_LIMIT).to_string(),
+                    }],
+                    original_size: Some(size),
+                }
+            }
+            other => other.clone(),
+        })
+        .collect();
+
+    MessageContent::Blocks(blocks)
+}
+
+fn truncate_str(text: &str, max_bytes: usize) -> &str {
+    let mut end = max_bytes.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::test_support::{message, tool_result};
+
+    fn messages(count: usize) -> Vec<Message> {
+        (0..count)
+            .map(|i| message(&format!("m{}", i), Vec::new()))
+            .collect()
+    }
+
+    fn ids(page: &SessionPage) -> Vec<&str> {
+        page.messages.iter().map(|m| m.id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_page_bounds() {
+        let all = messages(5);
+        let page_at = |offset, limit, after: Option<&str>| {
+            page(
+                "s".to_string(),
+                &all,
+                offset,
+                limit,
+                after.map(String::from),
+                true,
+            )
+        };
+
+        let first = page_at(None, Some(2), None).unwrap();
+        assert_eq!(
+            (ids(&first), first.total, first.offset),
+            (vec!["m0", "m1"], 5, 0)
+        );
+        assert_eq!(first.next_cursor.as_deref(), Some("m1"));
+
+        let next = page_at(None, Some(2), first.next_cursor.as_deref()).unwrap();
+        assert_eq!((ids(&next), next.offset), (vec!["m2", "m3"], 2));
+
+        let last = page_at(None, Some(2), Some("m3")).unwrap();
+        assert_eq!(
+            (ids(&last), last.next_cursor.as_deref()),
+            (vec!["m4"], None)
+        );
+
+        let exact = page_at(Some(3), Some(2), None).unwrap();
+        assert_eq!(
+            (ids(&exact), exact.next_cursor.as_deref()),
+            (vec!["m3", "m4"], None)
+        );
+
+        let past_end = page_at(Some(9), None, None).unwrap();
+        assert_eq!((past_end.offset, past_end.messages.len()), (5, 0));
+
+        let after_last = page_at(None, None, Some("m4")).unwrap();
+        assert!(after_last.messages.is_empty() && after_last.next_cursor.is_none());
+
+        // A zero limit still returns one message, so paging always advances
+        assert_eq!(ids(&page_at(None, Some(0), None).unwrap()), vec!["m0"]);
+        assert!(page_at(None, None, Some("missing")).is_err());
+        assert!(page("s".to_string(), &[], None, None, None, true)
+            .unwrap()
+            .messages
+            .is_empty());
+    }
+
+    #[test]
+    fn test_truncate_str_keeps_char_boundaries() {
+        assert_eq!(truncate_str("héllo", 2), "h");
+        assert_eq!(truncate_str("héllo", 3), "hé");
+        assert_eq!(truncate_str("abc", 10), "abc");
+        assert_eq!(truncate_str("€", 0), "");
+    }
+
+    #[test]
+    fn test_truncate_tool_results() {
+        // Multi-byte characters straddle the limit at every alignment
+        let long = "€".repeat(TOOL_RESULT_LIMIT);
+        let content = MessageContent::Blocks(vec![
+            tool_result("small", false, "ok"),
+            tool_result("large", true, &long),
+        ]);
+
+        let MessageContent::Blocks(blocks) = truncate_tool_results(&content) else {
+            panic!("expected blocks");
+        };
+
+        let json = |block: &ContentBlock| serde_json::to_value(block).unwrap();
+        assert_eq!(json(&blocks[0]), json(&tool_result("small", false, "ok")));
+        let ContentBlock::ToolResult {
+            tool_use_id,
+            is_error,
+            content,
+            original_size,
+        } = &blocks[1]
+        else {
+            panic!("expected a tool result");
+        };
+        let text = MessageContent::Blocks(content.clone()).text();
+        assert_eq!((tool_use_id.as_str(), *is_error), ("large", true));
+        assert!(text.len() <= TOOL_RESULT_LIMIT && text.len() > TOOL_RESULT_LIMIT - 3);
+        assert!(long.starts_with(&text));
+        assert!(original_size.unwrap() > long.len());
+    }
+}
@@ -0,0 +1,81 @@
+use crate::models::ShellCommand;
+use crate::parsers::extract_shell_commands;
+use crate::parsers::parse_session_jsonl;
+use crate::parsers::session_index::transcript_ids;
+use crate::parsers::session_parser::session_time_range;
+use crate::roots::{find_project, project_dirs};
+use chrono::{DateTime, Utc};
+use tauri::command;
+
+/// Lists Bash commands run by agents, most recent first.
+///
+/// Without `project_id` every project is searched. `since`/`until` bound the
+/// command start time, and `query` matches the command or its description,
+/// case-insensitively.
+#[command]
+pub fn list_shell_commands(
+    project_id: Option<String>,
+    session_id: Option<String>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+    query: Option<String>,
+) -> Result<Vec<ShellCommand>, String> {
+    let projects = match project_id {
+        Some(id) => vec![find_project(&id).map_err(|e| e.to_string())?],
+        None => project_dirs().map_err(|e| format!("Failed to read projects: {}", e))?,
+    };
+    let query = query
+        .map(|q| q.trim().to_lowercase())
+        .filter(|q| !q.is_empty());
+    let in_range = |time: DateTime<Utc>| {
+        since.is_none_or(|since| time >= since) && until.is_none_or(|until| time <= until)
+    };
+
+    let mut commands = Vec::new();
+
+    for project in projects {
+        let (project_id, project_path) = (project.id, project.path);
+        if !project_path.exists() {
+            return Err(format!("Project not found: {}", project_id));
+        }
+
+        let session_ids = match &session_id {
+            Some(id) => vec![id.clone()],
+            None => transcript_ids(&project_path).map_err(|e| e.to_string())?,
+        };
+
+        for session_id in session_ids {
+            let path = project_path.join(format!("{}.jsonl", session_id));
+
+            // Skip transcripts that lie entirely outside the range
+            if let Ok((first, last)) = session_time_range(&path) {
+                if since.is_some_and(|since| last < since)
+                    || until.is_some_and(|until| first > until)
+                {
+                    continue;
+                }
+            }
+            let Ok(session) = parse_session_jsonl(&session_id, &project_id, &path) else {
+                continue;
+            };
+
+            commands.extend(extract_shell_commands(&session).into_iter().filter(|cmd| {
+                let time_ok = match cmd.started_at {
+                    Some(time) => in_range(time),
+                    None => since.is_none() && until.is_none(),
+                };
+                let query_ok = query.as_deref().is_none_or(|q| {
+                    cmd.command.to_lowercase().contains(q)
+                        || cmd
+                            .description
+                            .as_deref()
+                            .is_some_and(|d| d.to_lowercase().contains(q))
+                });
+                time_ok && query_ok
+            }));
+        }
+    }
+
+    commands.sort_by_key(|c| std::cmp::Reverse(c.started_at));
+    Ok(commands)
+}
@@ -0,0 +1,107 @@
+use crate::commands::sessions::get_session;
+use crate::config::{CtxConfig, ModelPrice};
+use crate::models::usage::usage_by_model;
+use crate::models::{ModelUsage, ProjectUsage, SessionUsage, TokenUsage, UsageSummary};
+use crate::parsers::parse_session_jsonl;
+use crate::parsers::session_index::transcript_ids;
+use crate::roots::find_project;
+use std::collections::{BTreeMap, HashMap};
+use tauri::command;
+
+#[command]
+pub fn get_session_usage(project_id: String, session_id: String) -> Result<SessionUsage, String> {
+    let config = CtxConfig::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    let session = get_session(project_id, session_id, None)?;
+
+    Ok(SessionUsage {
+        summary: summarize(usage_by_model(&session.messages), &config),
+        session_id: session.id,
+    })
+}
+
+#[command]
+pub fn get_project_usage(project_id: String) -> Result<ProjectUsage, String> {
+    let config = CtxConfig::load().map_err(|e| format!("Failed to load config: {}", e))?;
+    let project_path = find_project(&project_id).map_err(|e| e.to_string())?.path;
+
+    if !project_path.exists() {
+        return Err(format!("Project not found: {}", project_id));
+    }
+
+    let mut totals: HashMap<String, TokenUsage> = HashMap::new();
+    let mut sessions = Vec::new();
+
+    for session_id in transcript_ids(&project_path).map_err(|e| e.to_string())? {
+        let path = project_path.join(format!("{}.jsonl", session_id));
+        let Ok(session) = parse_session_jsonl(&session_id, &project_id, &path) else {
+            continue;
+        };
+
+        let by_model = usage_by_model(&session.messages);
+        for (model, usage) in &by_model {
+            totals.entry(model.clone()).or_default().add(usage);
+        }
+        sessions.push(SessionUsage {
+            session_id: session.id,
+            summary: summarize(by_model, &config),
+        });
+    }
+
+    sessions.sort_by(|a, b| b.summary.cost_usd.total_cmp(&a.summary.cost_usd));
+
+    Ok(ProjectUsage {
+        project_id,
+        total: summarize(totals, &config),
+        sessions,
+    })
+}
+
+#[command]
+pub fn get_price_table() -> Result<BTreeMap<String, ModelPrice>, String> {
+    CtxConfig::load()
+        .map(|config| config.pricing)
+        .map_err(|e| format!("Failed to load config: {}", e))
+}
+
+/// Sets (or with `None`, removes) the price for a model name prefix.
+#[command]
+pub fn set_model_price(model: String, price: Option<ModelPrice>) -> Result<(), String> {
+    let mut config = CtxConfig::load().map_err(|e| format!("Failed to load config: {}", e))?;
+
+    match price {
+        Some(price) => {
+            config.pricing.insert(model, price);
+        }
+        None => {
+            config.pricing.remove(&model);
+        }
+    }
+
+    config
+        .save()
+        .map_err(|e| format!("Failed to save config: {}", e))
+}
+
+fn summarize(by_model: HashMap<String, TokenUsage>, config: &CtxConfig) -> UsageSummary {
+    let mut summary = UsageSummary::default();
+
+    for (model, usage) in by_model {
+        let cost_usd = config.price_for(&model).map(|price| price.cost(&usage));
+        match cost_usd {
+            Some(cost) => summary.cost_usd += cost,
+            // Synthetic entries (errors, interruptions) carry zero usage
+            None if usage.total() > 0 => summary.unpriced_models.push(model.clone()),
+            None => {}
+        }
+        summary.usage.add(&usage);
+        summary.by_model.push(ModelUsage {
+            model,
+            usage,
+            cost_usd,
+        });
+    }
+
+    summary.by_model.sort_by(|a, b| a.model.cmp(&b.model));
+    summary.unpriced_models.sort();
+    summary
+}
@@ -0,0 +1,20 @@
+use crate::watchers::service::{WatcherHealth, WatcherService};
+use tauri::command;
+use tauri::State;
+
+#[command]
+pub fn get_watcher_health(state: State<'_, WatcherService>) -> Result<Vec<WatcherHealth>, String> {
+    state.health()
+}
+
+#[command]
+pub fn pause_watcher(state: State<'_, WatcherService>) -> Result<(), String> {
+    state.pause();
+    Ok(())
+}
+
+#[command]
+pub fn resume_watcher(state: State<'_, WatcherService>) -> Result<(), String> {
+    state.resume();
+    Ok(())
+}
@@ -0,0 +1,142 @@
+use crate::models::TokenUsage;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// CTX's own data directory (`~/.ctx`). Nothing under `~/.claude` is ours.
+pub fn ctx_dir() -> Result<PathBuf> {
+    Ok(dirs::home_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
+        .join(".ctx"))
+}
+
+/// USD prices per million tokens for one model family.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPrice {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        ModelPrice {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        (usage.input_tokens as f64 * self.input
+            + usage.output_tokens as f64 * self.output
+            + usage.cache_creation_input_tokens as f64 * self.cache_write
+            + usage.cache_read_input_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Settings persisted in `~/.ctx/config.json`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CtxConfig {
+    /// Prices keyed by model name prefix, e.g. `claude-sonnet-4`.
+    pub pricing: BTreeMap<String, ModelPrice>,
+    /// Claude config directories to read besides the default one, keyed by
+    /// a short name that prefixes their project ids.
+    pub claude_dirs: BTreeMap<String, PathBuf>,
+}
+
+impl Default for CtxConfig {
+    fn default() -> Self {
+        CtxConfig {
+            pricing: default_pricing(),
+            claude_dirs: BTreeMap::new(),
+        }
+    }
+}
+
+impl CtxConfig {
+    pub fn path() -> Result<PathBuf> {
+        Ok(ctx_dir()?.join("config.json"))
+    }
+
+    /// Loads the config, falling back to defaults when the file does not exist.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Finds the price for `model` by longest matching prefix.
+    pub fn price_for(&self, model: &str) -> Option<&ModelPrice> {
+        self.pricing
+            .iter()
+            .filter(|(prefix, _)| model.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, price)| price)
+    }
+}
+
+fn default_pricing() -> BTreeMap<String, ModelPrice> {
+    [
+        ("claude-opus-4-5", ModelPrice::new(5.0, 25.0, 6.25, 0.5)),
+        ("claude-opus-4", ModelPrice::new(15.0, 75.0, 18.75, 1.5)),
+        ("claude-sonnet-4", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+        ("claude-haiku-4-5", ModelPrice::new(1.0, 5.0, 1.25, 0.1)),
+        ("claude-3-7-sonnet", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+        ("claude-3-5-sonnet", ModelPrice::new(3.0, 15.0, 3.75, 0.3)),
+        ("claude-3-5-haiku", ModelPrice::new(0.8, 4.0, 1.0, 0.08)),
+        ("claude-3-opus", ModelPrice::new(15.0, 75.0, 18.75, 1.5)),
+    ]
+    .into_iter()
+    .map(|(model, price)| (model.to_string(), price))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_price_for_longest_prefix() {
+        let config = CtxConfig::default();
+
+        let opus = config.price_for("claude-opus-4-1-20250805").unwrap();
+        assert_eq!(opus.input, 15.0);
+
+        let opus_45 = config.price_for("claude-opus-4-5-20251101").unwrap();
+        assert_eq!(opus_45.input, 5.0);
+
+        assert!(config.price_for("<synthetic>").is_none());
+    }
+
+    #[test]
+    fn test_model_price_cost() {
+        let price = ModelPrice::new(3.0, 15.0, 3.75, 0.3);
+        let usage = TokenUsage {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_input_tokens: 0,
+            cache_read_input_tokens: 1_000_000,
+        };
+        assert!((price.cost(&usage) - 4.8).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,190 @@
+/// Lines of context kept around each change, as in `diff -u`.
+const CONTEXT: usize = 3;
+/// Largest LCS table we are willing to build; bigger rewrites are rendered as
+/// a plain delete-then-insert of the differing region.
+const MAX_TABLE_CELLS: usize = 4_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Op {
+    Equal,
+    Delete,
+    Insert,
+}
+
+/// Renders the unified-diff hunks (`@@ ... @@` sections) turning `old` into
+/// `new`. Returns an empty string when the texts are identical.
+pub fn unified_hunks(old: &str, new: &str) -> String {
+    let old_lines: Vec<&str> = old.split_inclusive('\n').collect();
+    let new_lines: Vec<&str> = new.split_inclusive('\n').collect();
+    let ops = diff_lines(&old_lines, &new_lines);
+
+    let mut out = String::new();
+    for (start, end) in hunk_ranges(&ops) {
+        render_hunk(&ops, start, end, &mut out);
+    }
+    out
+}
+
+/// A complete `git apply`-able diff for one file. `old` is `None` for a file
+/// the patch creates.
+pub fn git_file_diff(path: &str, old: Option<&str>, new: &str) -> String {
+    let hunks = unified_hunks(old.unwrap_or(""), new);
+    if hunks.is_empty() && old.is_some() {
+        return String::new();
+    }
+
+    let mut out = format!("diff --git a/{0} b/{0}\n", path);
+    match old {
+        Some(_) => out.push_str(&format!("--- a/{}\n", path)),
+        None => {
+            out.push_str("new file mode 100644\n");
+            out.push_str("--- /dev/null\n");
+        }
+    }
+    out.push_str(&format!("+++ b/{}\n", path));
+    out.push_str(&hunks);
+    out
+}
+
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let prefix = old.iter().zip(new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut ops: Vec<(Op, &str)> = old[..prefix].iter().map(|l| (Op::Equal, *l)).collect();
+    ops.extend(lcs_diff(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    ));
+    ops.extend(old[old.len() - suffix..].iter().map(|l| (Op::Equal, *l)));
+    ops
+}
+
+fn lcs_diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(Op, &'a str)> {
+    let (n, m) = (old.len(), new.len());
+    let replace_all = || {
+        old.iter()
+            .map(|l| (Op::Delete, *l))
+            .chain(new.iter().map(|l| (Op::Insert, *l)))
+            .collect()
+    };
+    if n == 0 || m == 0 || (n + 1) * (m + 1) > MAX_TABLE_CELLS {
+        return replace_all();
+    }
+
+    // table[i][j] = length of the LCS of old[i..] and new[j..]
+    let width = m + 1;
+    let mut table = vec![0u32; (n + 1) * width];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i * width + j] = if old[i] == new[j] {
+                table[(i + 1) * width + j + 1] + 1
+            } else {
+                table[(i + 1) * width + j].max(table[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::with_capacity(n + m);
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if old[i] == new[j] {
+            ops.push((Op::Equal, old[i]));
+            i += 1;
+            j += 1;
+        } else if table[(i + 1) * width + j] >= table[i * width + j + 1] {
+            ops.push((Op::Delete, old[i]));
+            i += 1;
+        } else {
+            ops.push((Op::Insert, new[j]));
+            j += 1;
+        }
+    }
+    ops.extend(old[i..].iter().map(|l| (Op::Delete, *l)));
+    ops.extend(new[j..].iter().map(|l| (Op::Insert, *l)));
+    ops
+}
+
+/// Groups changes into `[start, end)` op ranges with surrounding context,
+/// merging changes whose context would overlap.
+fn hunk_ranges(ops: &[(Op, &str)]) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+
+    for (idx, _) in ops
+        .iter()
+        .enumerate()
+        .filter(|(_, (op, _))| *op != Op::Equal)
+    {
+        let start = idx.saturating_sub(CONTEXT);
+        let end = (idx + 1 + CONTEXT).min(ops.len());
+        match ranges.last_mut() {
+            Some(last) if start <= last.1 => last.1 = end,
+            _ => ranges.push((start, end)),
+        }
+    }
+
+    ranges
+}
+
+fn render_hunk(ops: &[(Op, &str)], start: usize, end: usize, out: &mut String) {
+    let old_before = count_side(&ops[..start], Op::Insert);
+    let new_before = count_side(&ops[..start], Op::Delete);
+    let old_len = count_side(&ops[start..end], Op::Insert);
+    let new_len = count_side(&ops[start..end], Op::Delete);
+
+    // An empty side is addressed by the line before it
+    let old_start = old_before + usize::from(old_len > 0);
+    let new_start = new_before + usize::from(new_len > 0);
+
+    out.push_str(&format!(
+        "@@ -{},{} +{},{} @@\n",
+        old_start, old_len, new_start, new_len
+    ));
+    for (op, line) in &ops[start..end] {
+        out.push(match op {
+            Op::Equal => ' ',
+            Op::Delete => '-',
+            Op::Insert => '+',
+        });
+        out.push_str(line);
+        if !line.ends_with('\n') {
+            out.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
+
+/// Lines on one side of the diff: everything except the other side's ops.
+fn count_side(ops: &[(Op, &str)], other: Op) -> usize {
+    ops.iter().filter(|(op, _)| *op != other).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unified_hunks() {
+        let old = "a\nb\nc\nd\ne\nf\ng\nh\ni\nj\nk\nl\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\nh\ni\nj\nk\nl\nm\n";
+
+        assert_eq!(
+            unified_hunks(old, new),
+            "@@ -1,7 +1,7 @@\n a\n b\n c\n-d\n+D\n e\n f\n g\n\
+             @@ -10,3 +10,4 @@\n j\n k\n l\n+m\n"
+        );
+        assert_eq!(unified_hunks(old, old), "");
+    }
+
+    #[test]
+    fn test_git_file_diff_new_file_without_trailing_newline() {
+        assert_eq!(
+            git_file_diff("src/new.rs", None, "fn main() {}"),
+            "diff --git a/src/new.rs b/src/new.rs\nnew file mode 100644\n--- /dev/null\n\
+             +++ b/src/new.rs\n@@ -0,0 +1,1 @@\n+fn main() {}\n\\ No newline at end of file\n"
+        );
+    }
+}
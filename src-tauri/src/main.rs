@@ -2,13 +2,19 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 mod commands;
+mod config;
+mod diff;
 mod models;
 mod parsers;
 mod process_manager;
+mod relocation;
+mod roots;
+mod store;
 mod watchers;
 
 use process_manager::ProcessManager;
 use tauri::Manager;
+use watchers::service::WatcherService;
 
 fn main() {
     tauri::Builder::default()
@@ -19,14 +25,41 @@ fn main() {
             commands::projects::scan_projects,
             commands::projects::get_project,
             commands::projects::rename_project,
+            commands::projects::relocate_project,
             commands::sessions::get_session,
             commands::sessions::list_sessions,
+            commands::sessions::set_session_title,
+            commands::sessions::get_session_delta,
+            commands::sessions::get_session_page,
+            commands::sessions::get_message,
+            commands::sessions::get_session_tree,
+            commands::sessions::get_tool_calls,
+            commands::sessions::validate_session,
+            commands::sessions::check_session_index,
+            commands::files::list_session_files,
+            commands::files::list_file_sessions,
+            commands::files::export_session_patch,
+            commands::shell::list_shell_commands,
+            commands::metadata::get_project_metadata,
+            commands::metadata::update_project_metadata,
+            commands::metadata::get_session_metadata,
+            commands::metadata::list_session_metadata,
+            commands::metadata::update_session_metadata,
+            commands::usage::get_session_usage,
+            commands::usage::get_project_usage,
+            commands::usage::get_price_table,
+            commands::usage::set_model_price,
+            commands::watcher::get_watcher_health,
+            commands::watcher::pause_watcher,
+            commands::watcher::resume_watcher,
             commands::live_sessions::spawn_claude_session,
+            commands::live_sessions::resume_claude_session,
             commands::live_sessions::terminate_session,
             commands::live_sessions::list_active_sessions,
             commands::live_sessions::get_active_session,
             commands::live_sessions::get_session_output,
             commands::live_sessions::send_input_to_session,
+            commands::live_sessions::get_session_stream,
         ])
         .setup(|app| {
             let handle = app.handle().clone();
@@ -35,11 +68,9 @@ fn main() {
             let process_manager = ProcessManager::new(handle.clone());
             app.manage(process_manager);
 
-            // Initialize file watchers
-            std::thread::spawn(move || {
-                watchers::session_watcher::start_watching(handle)
-                    .expect("Failed to start file watcher");
-            });
+            // Watch Claude's history for the lifetime of the app
+            let (roots, config_error) = roots::resolve_roots()?;
+            app.manage(WatcherService::start(handle, roots, config_error));
 
             Ok(())
         })
@@ -0,0 +1,34 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FileAccess {
+    /// Only looked at (Read, Glob, Grep).
+    Read,
+    /// Changed at least once (Edit, MultiEdit, Write, NotebookEdit).
+    Modified,
+}
+
+/// A file referenced by a session's tool calls.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionFile {
+    /// Absolute path, resolved against the session's cwd when relative.
+    pub path: String,
+    pub access: FileAccess,
+    pub read_count: usize,
+    pub modify_count: usize,
+    /// Tool names that referenced the file.
+    pub tools: Vec<String>,
+    pub first_touched: Option<DateTime<Utc>>,
+    pub last_touched: Option<DateTime<Utc>>,
+}
+
+/// A session that referenced a given file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileSession {
+    pub session_id: String,
+    pub path: String,
+    pub access: FileAccess,
+    pub last_touched: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,223 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Flags CTX sets from the spec's own fields or when resuming a session, so
+/// they cannot also be passed through `extra_args`.
+const MANAGED_FLAGS: &[&str] = &[
+    "--model",
+    "--permission-mode",
+    "--allowedTools",
+    "--allowed-tools",
+    "--disallowedTools",
+    "--disallowed-tools",
+    "--resume",
+    "-r",
+    "--continue",
+    "-c",
+    "--fork-session",
+    "--session-id",
+    "-p",
+    "--print",
+    "--output-format",
+    "--input-format",
+];
+
+/// How a live Claude Code session is started. Every field is optional; an
+/// empty spec starts a plain interactive session.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LaunchSpec {
+    pub mode: SessionMode,
+    /// First message sent to the agent.
+    pub prompt: Option<String>,
+    pub model: Option<String>,
+    pub permission_mode: Option<PermissionMode>,
+    /// Tool rules such as `Read` or `Bash(git log:*)`.
+    pub allowed_tools: Vec<String>,
+    pub disallowed_tools: Vec<String>,
+    /// Further CLI arguments, passed before the prompt.
+    pub extra_args: Vec<String>,
+    /// Variables added to the CLI's environment.
+    pub env: BTreeMap<String, String>,
+}
+
+/// How CTX talks to the CLI.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionMode {
+    /// Output is relayed line by line and the session state is guessed from
+    /// it. Works with any command.
+    #[default]
+    Raw,
+    /// Headless `stream-json` in both directions: the state, tool calls,
+    /// cost and result come from typed events, and input is sent as user
+    /// messages.
+    StreamJson,
+}
+
+/// Claude Code's `--permission-mode` values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PermissionMode {
+    Default,
+    AcceptEdits,
+    BypassPermissions,
+    Plan,
+}
+
+impl PermissionMode {
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            PermissionMode::Default => "default",
+            PermissionMode::AcceptEdits => "acceptEdits",
+            PermissionMode::BypassPermissions => "bypassPermissions",
+            PermissionMode::Plan => "plan",
+        }
+    }
+}
+
+impl LaunchSpec {
+    /// Checks the spec before anything is spawned, reporting every problem.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut problems = Vec::new();
+
+        if self.prompt.as_deref().is_some_and(|p| p.trim().is_empty()) {
+            problems.push("prompt is empty".to_string());
+        }
+        if let Some(model) = &self.model {
+            if model.is_empty() || model.starts_with('-') || model.contains(char::is_whitespace) {
+                problems.push(format!("invalid model: {:?}", model));
+            }
+        }
+        for tool in self.allowed_tools.iter().chain(&self.disallowed_tools) {
+            if tool.trim().is_empty() || tool.starts_with('-') {
+                problems.push(format!("invalid tool rule: {:?}", tool));
+            }
+        }
+        for arg in &self.extra_args {
+            let flag = arg.split('=').next().unwrap_or_default();
+            if MANAGED_FLAGS.contains(&flag) {
+                problems.push(format!("{} must be set through the launch spec", flag));
+            } else if arg == "--" {
+                problems.push("extra arguments cannot end the options".to_string());
+            }
+        }
+        for (key, value) in &self.env {
+            if key.is_empty() || key.contains(['=', '\0']) || value.contains('\0') {
+                problems.push(format!("invalid environment variable: {:?}", key));
+            }
+        }
+        if self.extra_args.iter().any(|arg| arg.contains('\0')) {
+            problems.push("extra arguments contain a NUL byte".to_string());
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Invalid launch spec: {}", problems.join("; ")))
+        }
+    }
+
+    /// The CLI arguments for this spec, the prompt last. In stream-json
+    /// mode the prompt is sent as the first input message instead.
+    pub fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+
+        if self.mode == SessionMode::StreamJson {
+            args.extend(
+                [
+                    "--print",
+                    "--output-format",
+                    "stream-json",
+                    "--input-format",
+                    "stream-json",
+                    // Required by stream-json output in print mode
+                    "--verbose",
+                ]
+                .map(String::from),
+            );
+        }
+        if let Some(model) = &self.model {
+            args.extend(["--model".to_string(), model.clone()]);
+        }
+        if let Some(mode) = self.permission_mode {
+            args.extend(["--permission-mode".to_string(), mode.as_arg().to_string()]);
+        }
+        if !self.allowed_tools.is_empty() {
+            args.push("--allowedTools".to_string());
+            args.extend(self.allowed_tools.iter().cloned());
+        }
+        if !self.disallowed_tools.is_empty() {
+            args.push("--disallowedTools".to_string());
+            args.extend(self.disallowed_tools.iter().cloned());
+        }
+        args.extend(self.extra_args.iter().cloned());
+        // `--` ends the variadic tool lists and keeps a prompt starting
+        // with `-` from being read as a flag
+        if let Some(prompt) = self
+            .prompt
+            .as_ref()
+            .filter(|_| self.mode == SessionMode::Raw)
+        {
+            args.extend(["--".to_string(), prompt.clone()]);
+        }
+
+        args
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_launch_spec_args() {
+        let spec = LaunchSpec {
+            prompt: Some("-v is broken".to_string()),
+            model: Some("sonnet".to_string()),
+            permission_mode: Some(PermissionMode::AcceptEdits),
+            allowed_tools: vec!["Read".to_string(), "Bash(git log:*)".to_string()],
+            extra_args: vec!["--verbose".to_string()],
+            ..Default::default()
+        };
+
+        assert!(spec.validate().is_ok());
+        assert_eq!(
+            spec.args(),
+            [
+                "--model",
+                "sonnet",
+                "--permission-mode",
+                "acceptEdits",
+                "--allowedTools",
+                "Read",
+                "Bash(git log:*)",
+                "--verbose",
+                "--",
+                "-v is broken",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_launch_spec_validation() {
+        let spec = LaunchSpec {
+            prompt: Some("  ".to_string()),
+            model: Some("--help".to_string()),
+            extra_args: vec!["--model=opus".to_string()],
+            env: BTreeMap::from([("A=B".to_string(), "1".to_string())]),
+            ..Default::default()
+        };
+
+        let err = spec.validate().unwrap_err();
+        assert_eq!(err.matches("; ").count(), 3, "{}", err);
+        assert!(LaunchSpec::default().validate().is_ok());
+
+        let stream = LaunchSpec {
+            mode: SessionMode::StreamJson,
+            prompt: Some("hi".to_string()),
+            ..Default::default()
+        };
+        assert!(!stream.args().contains(&"hi".to_string()));
+    }
+}
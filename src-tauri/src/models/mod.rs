@@ -1,5 +1,23 @@
+pub mod file_index;
+pub mod launch;
+pub mod patch;
 pub mod project;
+pub mod relocation;
 pub mod session;
+pub mod shell_command;
+pub mod tool_call;
+pub mod usage;
 
+pub use file_index::{FileAccess, FileSession, SessionFile};
+pub use launch::{LaunchSpec, SessionMode};
+pub use patch::{FileEdit, PatchFile, SessionPatch};
 pub use project::Project;
-pub use session::{Session, Message, MessageContent};
+pub use relocation::{RelocationFile, RelocationPlan, RelocationReport};
+pub use session::{
+    AgentState, ContentBlock, ConversationTree, DiagnosticKind, Message, MessageContent,
+    MessageHeader, ParseDiagnostic, Session, SessionDelta, SessionIndexReport, SessionListItem,
+    SessionPage, SessionValidation, TailCursor, Thread, ThreadKind,
+};
+pub use shell_command::{ShellCommand, ShellOutput};
+pub use tool_call::{ToolCall, ToolCallStatus};
+pub use usage::{ModelUsage, ProjectUsage, SessionUsage, TokenUsage, UsageSummary};
@@ -0,0 +1,45 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// One Edit, MultiEdit or Write call rendered as unified diff hunks.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEdit {
+    pub tool_call_id: String,
+    pub tool: String,
+    /// Absolute path, resolved against the session's cwd when relative.
+    pub path: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    /// `@@` hunks for this call alone.
+    pub diff: String,
+    /// False when the file's contents were unknown at this point, so the
+    /// hunk only covers the replaced text and its line numbers are relative.
+    pub exact: bool,
+}
+
+/// A file changed by a session, as it appears in the exported patch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatchFile {
+    pub path: String,
+    /// Path used in the patch headers, relative to the session's cwd.
+    pub patch_path: String,
+    /// The session created the file.
+    pub is_new: bool,
+    /// See [`FileEdit::exact`]. Inexact hunks have no surrounding context:
+    /// they apply with `git apply --unidiff-zero` when the replaced text
+    /// spans whole lines, and not at all for a replacement inside a line.
+    pub exact: bool,
+    /// The file could not be represented and is missing from the patch.
+    pub omitted: bool,
+    pub edit_count: usize,
+}
+
+/// The net effect of a session's file edits as a git patch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPatch {
+    pub session_id: String,
+    pub patch: String,
+    pub files: Vec<PatchFile>,
+    /// Every edit in the order it was made.
+    pub edits: Vec<FileEdit>,
+    pub warnings: Vec<String>,
+}
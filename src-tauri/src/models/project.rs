@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
 
@@ -5,10 +6,22 @@ use std::path::PathBuf;
 pub struct Project {
     pub id: String,
     pub name: String,
+    /// Name of the Claude root the project was found in.
+    pub root: String,
+    /// The project directory under the root's `projects` directory.
     pub path: PathBuf,
+    /// The repository the sessions ran in, when it could be resolved.
+    pub working_dir: Option<PathBuf>,
     pub sessions: Vec<String>,
-    pub created_at: String,
+    /// Start of the project's oldest session.
+    pub created_at: DateTime<Utc>,
+    /// Last activity in the project's newest session.
+    pub updated_at: DateTime<Utc>,
     pub is_favorite: bool,
+    /// The user's display name for the project, from CTX's metadata.
+    pub alias: Option<String>,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
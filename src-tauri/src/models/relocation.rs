@@ -0,0 +1,40 @@
+use super::Project;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// What relocating a project would change, computed without touching disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelocationPlan {
+    pub project_id: String,
+    /// Id of the directory Claude Code derives from the new working
+    /// directory, in the same root.
+    pub new_project_id: String,
+    pub source_dir: PathBuf,
+    pub target_dir: PathBuf,
+    pub old_working_dir: Option<PathBuf>,
+    pub new_working_dir: PathBuf,
+    /// Transcripts with `cwd` fields to rewrite.
+    pub files: Vec<RelocationFile>,
+    pub rewritten_lines: usize,
+    /// Problems that prevent the relocation; it only runs when empty.
+    pub blockers: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelocationFile {
+    /// Path relative to the project directory.
+    pub path: String,
+    pub rewritten_lines: usize,
+    pub total_lines: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RelocationReport {
+    pub plan: RelocationPlan,
+    pub dry_run: bool,
+    /// Copy of the original project directory under `~/.ctx/backups`.
+    pub backup_dir: Option<PathBuf>,
+    /// The relocated project, when the relocation ran.
+    pub project: Option<Project>,
+}
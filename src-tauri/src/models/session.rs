@@ -1,14 +1,160 @@
+use super::{ShellOutput, TokenUsage};
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Session {
     pub id: String,
     pub project_id: String,
+    /// Display title: a user-assigned title, the transcript summary, or the
+    /// first prompt, falling back to the session id.
     pub name: String,
+    /// Latest `"type": "summary"` entry written by Claude Code.
+    pub summary: Option<String>,
     pub messages: Vec<Message>,
-    pub created_at: String,
-    pub updated_at: String,
+    /// Timestamp of the earliest entry, or the file's creation time.
+    pub created_at: DateTime<Utc>,
+    /// Timestamp of the latest entry, or the file's modification time.
+    pub updated_at: DateTime<Utc>,
     pub is_waiting: bool,
+    /// Working directory Claude Code was running in, from the transcript.
+    pub cwd: Option<String>,
+    /// Git branch recorded on the most recent entry that carried one.
+    pub git_branch: Option<String>,
+    /// Claude Code CLI version recorded on the most recent entry.
+    pub version: Option<String>,
+    /// Tokens used by all assistant responses in the session.
+    pub usage: TokenUsage,
+    /// Lines that could not be parsed and were skipped.
+    pub diagnostics: Vec<ParseDiagnostic>,
+}
+
+/// A transcript line that could not be turned into an entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParseDiagnostic {
+    /// 1-based line number in the file.
+    pub line: usize,
+    /// Byte offset of the start of the line.
+    pub byte_offset: u64,
+    pub kind: DiagnosticKind,
+    pub message: String,
+    /// The beginning of the offending line.
+    pub snippet: String,
+}
+
+/// Result of checking one session file for malformed lines.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionValidation {
+    pub session_id: String,
+    pub message_count: usize,
+    pub diagnostics: Vec<ParseDiagnostic>,
+    /// Set when the file could not be read at all.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticKind {
+    InvalidJson,
+    /// The line ends mid-value, typically a write cut short.
+    Truncated,
+    InvalidUtf8,
+    NotAnObject,
+    /// A message entry without the fields it needs.
+    MissingField,
+}
+
+/// Entry in a project's session list.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionListItem {
+    pub id: String,
+    pub title: String,
+    pub pinned: bool,
+    pub tags: Vec<String>,
+    /// Listed in `sessions-index.json`.
+    pub in_index: bool,
+    /// The `<id>.jsonl` transcript exists.
+    pub on_disk: bool,
+    pub size_bytes: u64,
+    pub modified_at: Option<DateTime<Utc>>,
+    pub message_count: usize,
+}
+
+/// How a project's `sessions-index.json` compares to its transcripts.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionIndexReport {
+    pub project_id: String,
+    pub index_exists: bool,
+    /// Why the index could not be read, if it exists but is broken.
+    pub index_error: Option<String>,
+    pub indexed: usize,
+    pub on_disk: usize,
+    /// In the index, but the transcript is gone.
+    pub missing_files: Vec<String>,
+    /// Transcripts the index does not list.
+    pub unindexed: Vec<String>,
+    /// Transcripts modified after the index last recorded them.
+    pub stale: Vec<String>,
+}
+
+/// Lightweight view of a message for listing long sessions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageHeader {
+    pub id: String,
+    pub role: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub uuid: Option<String>,
+    pub parent_uuid: Option<String>,
+    pub is_sidechain: bool,
+    pub model: Option<String>,
+    /// Start of the message text.
+    pub preview: String,
+    /// Size in bytes of the full serialized content.
+    pub content_size: usize,
+    /// Content with large tool results truncated; `None` in headers-only mode.
+    pub content: Option<MessageContent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionPage {
+    pub session_id: String,
+    /// Number of messages in the whole session.
+    pub total: usize,
+    /// Index of the first message in this page.
+    pub offset: usize,
+    pub messages: Vec<MessageHeader>,
+    /// Id to pass as `after` to fetch the next page, if there is one.
+    pub next_cursor: Option<String>,
+}
+
+/// Where an incremental read of a session file left off.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TailCursor {
+    /// Byte offset up to which the file has been consumed.
+    pub offset: u64,
+    /// Bytes of a trailing line that had not been terminated yet.
+    pub carry: Vec<u8>,
+    /// Messages produced so far, used to number entries without a uuid.
+    pub message_count: usize,
+    /// Complete lines consumed so far, used to number diagnostics.
+    #[serde(default)]
+    pub line_count: usize,
+    /// Parents of the non-message entries seen so far, so that messages
+    /// appended later are relinked as in a full parse.
+    #[serde(default)]
+    pub links: HashMap<String, Option<String>>,
+}
+
+/// Messages appended to a session file since a previous [`TailCursor`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionDelta {
+    pub messages: Vec<Message>,
+    pub is_waiting: bool,
+    /// Set when the file shrank and was re-read from the start.
+    pub reset: bool,
+    pub diagnostics: Vec<ParseDiagnostic>,
+    pub cursor: TailCursor,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,14 +162,90 @@ pub struct Message {
     pub id: String,
     pub role: String,
     pub content: MessageContent,
-    pub timestamp: String,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub uuid: Option<String>,
+    pub parent_uuid: Option<String>,
+    pub session_id: Option<String>,
+    pub cwd: Option<String>,
+    pub git_branch: Option<String>,
+    pub version: Option<String>,
+    /// True for messages of a subagent conversation (Task tool).
+    pub is_sidechain: bool,
+    /// True for entries Claude Code injects rather than the user typing them.
+    pub is_meta: bool,
+    /// Model that produced an assistant message.
+    pub model: Option<String>,
+    /// Token usage of the API response this message belongs to.
+    pub usage: Option<TokenUsage>,
+    /// API message id, shared by all entries of one streamed response.
+    pub api_message_id: Option<String>,
+    /// Structured output of the Bash call this entry answers. Only used to
+    /// build the shell history, so it is not sent along with the message.
+    #[serde(skip)]
+    pub shell_output: Option<ShellOutput>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum MessageContent {
     Text(String),
-    Object(serde_json::Value),
+    Blocks(Vec<ContentBlock>),
+}
+
+impl MessageContent {
+    pub fn blocks(&self) -> &[ContentBlock] {
+        match self {
+            MessageContent::Text(_) => &[],
+            MessageContent::Blocks(blocks) => blocks,
+        }
+    }
+
+    /// Plain text of the message: the string content, or all text blocks joined.
+    pub fn text(&self) -> String {
+        match self {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Blocks(blocks) => blocks
+                .iter()
+                .filter_map(|b| match b {
+                    ContentBlock::Text { text } => Some(text.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        thinking: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        is_error: bool,
+        content: Vec<ContentBlock>,
+        /// Size in bytes of the full content when `content` was truncated for listing.
+        original_size: Option<usize>,
+    },
+    Image {
+        media_type: Option<String>,
+        data: Option<String>,
+        url: Option<String>,
+    },
+    /// Any block type we don't model yet, kept verbatim.
+    Unknown {
+        raw: serde_json::Value,
+    },
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +255,31 @@ pub struct Thread {
     pub name: String,
     pub agents: Vec<AgentState>,
     pub message_count: usize,
+    pub kind: ThreadKind,
+    /// Message ids from the thread's first message to its leaf.
+    pub message_ids: Vec<String>,
+    /// Message the thread diverged from, if it is not a root conversation.
+    pub fork_point: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadKind {
+    /// The path leading to the session's latest message.
+    Main,
+    /// A path abandoned after an edit or rewind.
+    Branch,
+    /// A subagent conversation.
+    Sidechain,
+}
+
+/// A session's messages arranged by their `uuid`/`parentUuid` links.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversationTree {
+    pub session_id: String,
+    pub main: Thread,
+    pub branches: Vec<Thread>,
+    pub sidechains: Vec<Thread>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,38 @@
+use super::ToolCallStatus;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A command an agent ran through the Bash tool.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ShellCommand {
+    pub tool_call_id: String,
+    pub project_id: String,
+    pub session_id: String,
+    pub command: String,
+    /// The agent's own summary of what the command does.
+    pub description: Option<String>,
+    /// Working directory of the session when the command was issued.
+    pub cwd: Option<String>,
+    pub status: ToolCallStatus,
+    /// `Some(0)` for successful runs, the reported code for failed ones.
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub interrupted: bool,
+    pub run_in_background: bool,
+    /// Start of the combined stdout/stderr.
+    pub output_preview: Option<String>,
+    /// Start of each stream, when Claude Code recorded them separately.
+    pub stdout_preview: Option<String>,
+    pub stderr_preview: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    pub is_sidechain: bool,
+}
+
+/// The `toolUseResult` Claude Code records next to a Bash result.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ShellOutput {
+    pub stdout: String,
+    pub stderr: String,
+    pub interrupted: bool,
+}
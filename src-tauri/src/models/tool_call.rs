@@ -0,0 +1,31 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A `tool_use` block paired with the `tool_result` that answered it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// The `tool_use` id shared by the call and its result.
+    pub id: String,
+    pub name: String,
+    pub input: serde_json::Value,
+    pub status: ToolCallStatus,
+    /// Assistant message that issued the call.
+    pub message_id: String,
+    /// User message that carried the result.
+    pub result_message_id: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    /// Start of the result text.
+    pub result_preview: Option<String>,
+    pub is_sidechain: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallStatus {
+    /// No result recorded (still running, interrupted, or cut off).
+    Pending,
+    Success,
+    Error,
+}
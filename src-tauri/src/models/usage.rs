@@ -0,0 +1,84 @@
+use super::Message;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Token counts as reported in an assistant entry's `usage` object.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_input_tokens: u64,
+    pub cache_read_input_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_input_tokens += other.cache_creation_input_tokens;
+        self.cache_read_input_tokens += other.cache_read_input_tokens;
+    }
+
+    pub fn total(&self) -> u64 {
+        self.input_tokens
+            + self.output_tokens
+            + self.cache_creation_input_tokens
+            + self.cache_read_input_tokens
+    }
+}
+
+/// Usage per model across a set of messages, counting each API response once.
+///
+/// Claude Code writes one transcript entry per content block, each repeating
+/// the response's `usage`; the last entry of a response carries the final
+/// counts, so that is the one kept.
+pub fn usage_by_model(messages: &[Message]) -> HashMap<String, TokenUsage> {
+    let mut responses: HashMap<&str, (&str, TokenUsage)> = HashMap::new();
+    let mut unkeyed = Vec::new();
+
+    for msg in messages {
+        let Some(usage) = msg.usage else { continue };
+        let model = msg.model.as_deref().unwrap_or("unknown");
+        match msg.api_message_id.as_deref() {
+            Some(api_id) => {
+                responses.insert(api_id, (model, usage));
+            }
+            None => unkeyed.push((model, usage)),
+        }
+    }
+
+    let mut by_model: HashMap<String, TokenUsage> = HashMap::new();
+    for (model, usage) in responses.into_values().chain(unkeyed) {
+        by_model.entry(model.to_string()).or_default().add(&usage);
+    }
+    by_model
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelUsage {
+    pub model: String,
+    pub usage: TokenUsage,
+    /// `None` when the price table has no entry for this model.
+    pub cost_usd: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageSummary {
+    pub usage: TokenUsage,
+    pub cost_usd: f64,
+    pub by_model: Vec<ModelUsage>,
+    pub unpriced_models: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionUsage {
+    pub session_id: String,
+    pub summary: UsageSummary,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProjectUsage {
+    pub project_id: String,
+    pub total: UsageSummary,
+    pub sessions: Vec<SessionUsage>,
+}
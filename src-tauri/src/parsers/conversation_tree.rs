@@ -0,0 +1,182 @@
+use crate::models::{AgentState, ConversationTree, Message, Session, Thread, ThreadKind};
+use std::collections::{HashMap, HashSet};
+
+/// Arranges a session's messages into its main line, abandoned branches and
+/// sidechains using the `uuid`/`parentUuid` links.
+///
+/// The main line is the path ending at the latest non-sidechain message. Every
+/// other leaf is walked back until it reaches a message that already belongs to
+/// a thread, which becomes that thread's fork point.
+pub fn build_conversation_tree(session: &Session) -> ConversationTree {
+    // Legacy transcripts have no links; everything is one line in file order.
+    if session.messages.iter().all(|m| m.uuid.is_none()) {
+        let ids = session.messages.iter().map(|m| m.id.clone()).collect();
+        return ConversationTree {
+            session_id: session.id.clone(),
+            main: make_thread(session, ThreadKind::Main, 0, ids, None),
+            branches: Vec::new(),
+            sidechains: Vec::new(),
+        };
+    }
+
+    let by_id: HashMap<&str, &Message> = session
+        .messages
+        .iter()
+        .map(|m| (m.id.as_str(), m))
+        .collect();
+    let parents: HashSet<&str> = session
+        .messages
+        .iter()
+        .filter_map(|m| m.parent_uuid.as_deref())
+        .filter(|p| by_id.contains_key(p))
+        .collect();
+
+    let mut claimed = HashSet::new();
+
+    let main_ids = session
+        .messages
+        .iter()
+        .rev()
+        .find(|m| !m.is_sidechain)
+        .map(|leaf| claim_path(leaf, &by_id, &mut claimed).0)
+        .unwrap_or_default();
+
+    let mut branches = Vec::new();
+    let mut sidechains = Vec::new();
+
+    for leaf in session
+        .messages
+        .iter()
+        .filter(|m| !parents.contains(m.id.as_str()))
+    {
+        if claimed.contains(leaf.id.as_str()) {
+            continue;
+        }
+
+        let (ids, fork_point) = claim_path(leaf, &by_id, &mut claimed);
+        if leaf.is_sidechain {
+            let thread = make_thread(session, ThreadKind::Sidechain, sidechains.len(), ids, fork_point);
+            sidechains.push(thread);
+        } else {
+            let thread = make_thread(session, ThreadKind::Branch, branches.len(), ids, fork_point);
+            branches.push(thread);
+        }
+    }
+
+    ConversationTree {
+        session_id: session.id.clone(),
+        main: make_thread(session, ThreadKind::Main, 0, main_ids, None),
+        branches,
+        sidechains,
+    }
+}
+
+/// Walks from `leaf` towards the root, claiming every message on the way, and
+/// stops at the first message that was already claimed (the fork point).
+fn claim_path<'a>(
+    leaf: &'a Message,
+    by_id: &HashMap<&'a str, &'a Message>,
+    claimed: &mut HashSet<&'a str>,
+) -> (Vec<String>, Option<String>) {
+    let mut ids = Vec::new();
+    let mut fork_point = None;
+    let mut current = Some(leaf);
+
+    while let Some(msg) = current {
+        if claimed.contains(msg.id.as_str()) {
+            fork_point = Some(msg.id.clone());
+            break;
+        }
+        claimed.insert(msg.id.as_str());
+        ids.push(msg.id.clone());
+        current = msg
+            .parent_uuid
+            .as_deref()
+            .and_then(|p| by_id.get(p).copied());
+    }
+
+    ids.reverse();
+    (ids, fork_point)
+}
+
+fn make_thread(
+    session: &Session,
+    kind: ThreadKind,
+    index: usize,
+    message_ids: Vec<String>,
+    fork_point: Option<String>,
+) -> Thread {
+    let name = match kind {
+        ThreadKind::Main => "Main".to_string(),
+        ThreadKind::Branch => format!("Branch {}", index + 1),
+        ThreadKind::Sidechain => format!("Sidechain {}", index + 1),
+    };
+    let id = message_ids
+        .last()
+        .cloned()
+        .unwrap_or_else(|| format!("{}-main", session.id));
+    let status = if kind == ThreadKind::Main && session.is_waiting {
+        "waiting"
+    } else {
+        "idle"
+    };
+
+    Thread {
+        id: id.clone(),
+        session_id: session.id.clone(),
+        name,
+        agents: vec![AgentState {
+            id,
+            name: match kind {
+                ThreadKind::Sidechain => "subagent".to_string(),
+                _ => "claude".to_string(),
+            },
+            status: status.to_string(),
+        }],
+        message_count: message_ids.len(),
+        kind,
+        message_ids,
+        fork_point,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::test_support::{message, session};
+
+    fn entry(uuid: &str, parent: Option<&str>, is_sidechain: bool) -> Message {
+        Message {
+            role: "user".to_string(),
+            parent_uuid: parent.map(|p| p.to_string()),
+            is_sidechain,
+            ..message(uuid, Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_build_conversation_tree() {
+        // a - b - c (abandoned)
+        //      \- d - e (main)
+        // s1 - s2 (sidechain)
+        let session = session(vec![
+            entry("a", None, false),
+            entry("b", Some("a"), false),
+            entry("c", Some("b"), false),
+            entry("s1", None, true),
+            entry("s2", Some("s1"), true),
+            entry("d", Some("b"), false),
+            entry("e", Some("d"), false),
+        ]);
+
+        let tree = build_conversation_tree(&session);
+
+        assert_eq!(tree.main.message_ids, ["a", "b", "d", "e"]);
+        assert_eq!(tree.branches.len(), 1);
+        assert_eq!(tree.branches[0].message_ids, ["c"]);
+        assert_eq!(tree.branches[0].fork_point.as_deref(), Some("b"));
+        assert_eq!(tree.sidechains.len(), 1);
+        assert_eq!(tree.sidechains[0].message_ids, ["s1", "s2"]);
+        assert_eq!(tree.sidechains[0].kind, ThreadKind::Sidechain);
+    }
+}
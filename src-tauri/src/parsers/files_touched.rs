@@ -0,0 +1,132 @@
+use crate::models::{FileAccess, Message, SessionFile, ToolCallStatus};
+use crate::parsers::extract_tool_calls;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Collects every file path referenced by the session's file tools, merged
+/// per path and sorted by path. Calls that failed are ignored, since a
+/// rejected edit did not change anything.
+pub fn extract_session_files(messages: &[Message]) -> Vec<SessionFile> {
+    let cwd_by_message: BTreeMap<&str, &str> = messages
+        .iter()
+        .filter_map(|m| m.cwd.as_deref().map(|cwd| (m.id.as_str(), cwd)))
+        .collect();
+    let mut files: BTreeMap<String, SessionFile> = BTreeMap::new();
+
+    for call in extract_tool_calls(messages) {
+        if call.status == ToolCallStatus::Error {
+            continue;
+        }
+        let Some((key, access)) = file_reference(&call.name) else {
+            continue;
+        };
+        let Some(raw_path) = call.input.get(key).and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let cwd = cwd_by_message.get(call.message_id.as_str()).copied();
+        let path = resolve_path(raw_path, cwd);
+
+        let file = files.entry(path.clone()).or_insert_with(|| SessionFile {
+            path,
+            access,
+            read_count: 0,
+            modify_count: 0,
+            tools: Vec::new(),
+            first_touched: call.started_at,
+            last_touched: call.started_at,
+        });
+        match access {
+            FileAccess::Read => file.read_count += 1,
+            FileAccess::Modified => file.modify_count += 1,
+        }
+        file.access = file.access.max(access);
+        if !file.tools.contains(&call.name) {
+            file.tools.push(call.name.clone());
+        }
+        if call.started_at.is_some() {
+            file.first_touched = file.first_touched.min(call.started_at).or(call.started_at);
+            file.last_touched = file.last_touched.max(call.started_at);
+        }
+    }
+
+    files.into_values().collect()
+}
+
+/// Input key holding the path for each file tool, and how the tool uses it.
+/// Glob and Grep take an optional search directory rather than a file.
+fn file_reference(tool: &str) -> Option<(&'static str, FileAccess)> {
+    match tool {
+        "Read" => Some(("file_path", FileAccess::Read)),
+        "Glob" | "Grep" => Some(("path", FileAccess::Read)),
+        "Edit" | "MultiEdit" | "Write" => Some(("file_path", FileAccess::Modified)),
+        "NotebookEdit" => Some(("notebook_path", FileAccess::Modified)),
+        _ => None,
+    }
+}
+
+pub(crate) fn resolve_path(path: &str, cwd: Option<&str>) -> String {
+    match cwd {
+        Some(cwd) if Path::new(path).is_relative() => {
+            Path::new(cwd).join(path).to_string_lossy().into_owned()
+        }
+        _ => path.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ContentBlock;
+    use crate::parsers::test_support::{message, tool_use};
+
+    #[test]
+    fn test_extract_session_files() {
+        let messages = vec![
+            message(
+                "a",
+                vec![
+                    tool_use(
+                        "t1",
+                        "Read",
+                        serde_json::json!({"file_path": "/repo/src/lib.rs"}),
+                    ),
+                    tool_use(
+                        "t2",
+                        "Edit",
+                        serde_json::json!({"file_path": "/repo/src/lib.rs"}),
+                    ),
+                    tool_use(
+                        "t3",
+                        "Grep",
+                        serde_json::json!({"pattern": "fn", "path": "src"}),
+                    ),
+                    tool_use(
+                        "t4",
+                        "Write",
+                        serde_json::json!({"file_path": "/repo/new.rs"}),
+                    ),
+                    tool_use("t5", "Bash", serde_json::json!({"command": "ls"})),
+                ],
+            ),
+            message(
+                "b",
+                vec![ContentBlock::ToolResult {
+                    tool_use_id: "t4".to_string(),
+                    is_error: true,
+                    content: Vec::new(),
+                    original_size: None,
+                }],
+            ),
+        ];
+
+        let files = extract_session_files(&messages);
+        let paths: Vec<_> = files.iter().map(|f| f.path.as_str()).collect();
+
+        assert_eq!(paths, ["/repo/src", "/repo/src/lib.rs"]);
+        assert_eq!(files[0].access, FileAccess::Read);
+        assert_eq!(files[1].access, FileAccess::Modified);
+        assert_eq!(files[1].read_count, 1);
+        assert_eq!(files[1].modify_count, 1);
+        assert_eq!(files[1].tools, ["Read", "Edit"]);
+    }
+}
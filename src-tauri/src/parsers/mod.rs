@@ -1,3 +1,22 @@
+pub mod conversation_tree;
+pub mod files_touched;
+pub mod project_dir;
+pub mod session_index;
 pub mod session_parser;
+pub mod session_patch;
+pub mod shell_commands;
+pub mod stream_json;
+pub mod tool_calls;
+pub mod transcript_cache;
 
-pub use session_parser::parse_session_jsonl;
+#[cfg(test)]
+pub(crate) mod test_support;
+
+pub use conversation_tree::build_conversation_tree;
+pub use files_touched::extract_session_files;
+pub use session_parser::{
+    parse_session_jsonl, parse_session_jsonl_with, parse_session_tail, tail_cursor_at, ParseOptions,
+};
+pub use session_patch::build_session_patch;
+pub use shell_commands::extract_shell_commands;
+pub use tool_calls::extract_tool_calls;
@@ -0,0 +1,129 @@
+use super::session_index::transcript_ids;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Lines read from the start of a transcript when looking for its `cwd`.
+const HEAD_LINES: usize = 50;
+
+/// Resolves the real working directory of a project directory under
+/// `~/.claude/projects`.
+///
+/// The `cwd` recorded in the project's transcripts wins, preferring one that
+/// encodes back to the directory name (a session may have started in a
+/// subdirectory). Otherwise the dash-encoded name is decoded against the
+/// filesystem, which only succeeds if the directory still exists.
+pub fn resolve_working_dir(project_path: &Path) -> Option<PathBuf> {
+    let name = project_path.file_name()?.to_str()?;
+
+    let mut transcripts: Vec<(PathBuf, std::time::SystemTime)> = transcript_ids(project_path)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|id| project_path.join(format!("{}.jsonl", id)))
+        .filter_map(|path| {
+            let modified = fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+            Some((path, modified))
+        })
+        .collect();
+    // Newest first, so a moved repository resolves to where it lives now
+    transcripts.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+
+    let mut fallback = None;
+    for (path, _) in transcripts {
+        let Some(cwd) = transcript_cwd(&path) else {
+            continue;
+        };
+        if encode_project_dir(&cwd) == name {
+            return Some(PathBuf::from(cwd));
+        }
+        fallback.get_or_insert(cwd);
+    }
+
+    fallback
+        .map(PathBuf::from)
+        .or_else(|| decode_project_dir(name))
+}
+
+/// Claude Code's directory name for a working directory: every character
+/// other than an ASCII letter or digit becomes `-`.
+pub fn encode_project_dir(path: &str) -> String {
+    path.chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect()
+}
+
+/// Decodes a project directory name by walking the filesystem from the root
+/// and matching each entry's encoded name against the remaining dashes, since
+/// a `-` may stand for a path separator, a dot or a literal dash.
+pub fn decode_project_dir(name: &str) -> Option<PathBuf> {
+    // Windows drive paths encode as `C--Users-...`
+    let bytes = name.as_bytes();
+    if bytes.len() > 3 && bytes[0].is_ascii_alphabetic() && bytes[1..3] == *b"--" {
+        let root = PathBuf::from(format!("{}:\\", &name[..1]));
+        return decode_under(&root, &name[3..]);
+    }
+
+    decode_under(Path::new("/"), name.strip_prefix('-')?)
+}
+
+fn decode_under(dir: &Path, rest: &str) -> Option<PathBuf> {
+    if rest.is_empty() {
+        return Some(dir.to_path_buf());
+    }
+
+    let mut candidates: Vec<(String, PathBuf)> = fs::read_dir(dir)
+        .ok()?
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| {
+            let encoded = encode_project_dir(e.file_name().to_str()?);
+            Some((encoded, e.path()))
+        })
+        .collect();
+    // Longest names first, so `my-app` is tried before `my`
+    candidates.sort_by_key(|(encoded, _)| std::cmp::Reverse(encoded.len()));
+
+    candidates.into_iter().find_map(|(encoded, path)| {
+        if rest == encoded {
+            Some(path)
+        } else {
+            let remaining = rest.strip_prefix(&encoded)?.strip_prefix('-')?;
+            decode_under(&path, remaining)
+        }
+    })
+}
+
+/// The first `cwd` recorded near the start of a transcript.
+fn transcript_cwd(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    BufReader::new(file)
+        .lines()
+        .take(HEAD_LINES)
+        .map_while(|line| line.ok())
+        .find_map(|line| {
+            let value: serde_json::Value = serde_json::from_str(&line).ok()?;
+            let cwd = value.get("cwd")?.as_str()?;
+            (!cwd.is_empty()).then(|| cwd.to_string())
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_project_dir() {
+        let root = std::env::temp_dir().join(format!("ctx-decode-{}", std::process::id()));
+        let repo = root.join("my-app").join(".config").join("api_v2");
+        fs::create_dir_all(&repo).unwrap();
+        fs::create_dir_all(root.join("my")).unwrap();
+
+        let encoded = encode_project_dir(repo.to_str().unwrap());
+        assert!(encoded.ends_with("-my-app--config-api-v2"));
+        assert_eq!(decode_project_dir(&encoded), Some(repo.clone()));
+        assert_eq!(decode_project_dir(&format!("{}-missing", encoded)), None);
+        assert_eq!(decode_project_dir("a€x"), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,218 @@
+use crate::parsers::session_parser::is_message_line;
+use crate::parsers::transcript_cache::TranscriptCache;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Message counts for `discover_sessions`, which runs on every session list.
+static MESSAGE_COUNTS: TranscriptCache<usize> = TranscriptCache::new();
+
+/// A session known from `sessions-index.json`, from a transcript on disk, or
+/// from both.
+#[derive(Debug, Clone)]
+pub struct DiscoveredSession {
+    pub id: String,
+    pub in_index: bool,
+    pub on_disk: bool,
+    /// Transcript size in bytes; 0 when the file is missing.
+    pub size: u64,
+    pub modified: Option<DateTime<Utc>>,
+    /// User and assistant entries in the transcript, or the index's count
+    /// when the file is missing.
+    pub message_count: usize,
+    /// When the index last saw the transcript, if it records that.
+    pub index_modified: Option<DateTime<Utc>>,
+}
+
+/// The sessions listed in a project's `sessions-index.json`, or `None` when
+/// the project has no index. Accepts both the `sessions`/`id` layout and
+/// Claude Code's `entries`/`sessionId` layout.
+pub fn read_session_index(project_path: &Path) -> Result<Option<Vec<Value>>> {
+    let index_path = project_path.join("sessions-index.json");
+    if !index_path.exists() {
+        return Ok(None);
+    }
+
+    let json: Value = serde_json::from_str(&fs::read_to_string(&index_path)?)?;
+    let entries = json
+        .get("sessions")
+        .or_else(|| json.get("entries"))
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    Ok(Some(entries))
+}
+
+/// Merges the session index with the `*.jsonl` files in the project
+/// directory, sorted by id. An unreadable index is treated as empty; use
+/// [`read_session_index`] to surface its error.
+pub fn discover_sessions(project_path: &Path) -> Result<Vec<DiscoveredSession>> {
+    let mut sessions: BTreeMap<String, DiscoveredSession> = BTreeMap::new();
+
+    for entry in read_session_index(project_path)
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+    {
+        let Some(id) = entry
+            .get("id")
+            .or_else(|| entry.get("sessionId"))
+            .and_then(|v| v.as_str())
+        else {
+            continue;
+        };
+        sessions.insert(
+            id.to_string(),
+            DiscoveredSession {
+                id: id.to_string(),
+                in_index: true,
+                on_disk: false,
+                size: 0,
+                modified: None,
+                message_count: entry
+                    .get("messageCount")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0) as usize,
+                index_modified: index_time(&entry),
+            },
+        );
+    }
+
+    for entry in fs::read_dir(project_path)?.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jsonl") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+
+        let session = sessions
+            .entry(id.to_string())
+            .or_insert_with(|| DiscoveredSession {
+                id: id.to_string(),
+                in_index: false,
+                on_disk: true,
+                size: 0,
+                modified: None,
+                message_count: 0,
+                index_modified: None,
+            });
+        session.on_disk = true;
+        session.size = metadata.len();
+        session.modified = metadata.modified().ok().map(DateTime::from);
+        session.message_count = MESSAGE_COUNTS
+            .get(&path, count_messages)
+            .unwrap_or(session.message_count);
+    }
+
+    Ok(sessions.into_values().collect())
+}
+
+/// Ids of the `<session_id>.jsonl` transcripts in a project directory, sorted.
+pub fn transcript_ids(project_path: &Path) -> Result<Vec<String>> {
+    let mut ids: Vec<String> = fs::read_dir(project_path)?
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(String::from))
+        .collect();
+    ids.sort();
+    Ok(ids)
+}
+
+/// Counts the lines a full parse turns into messages, without keeping them.
+fn count_messages(path: &Path) -> Result<usize> {
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut line = Vec::new();
+    let mut count = 0;
+
+    while reader.read_until(b'\n', &mut line)? > 0 {
+        if is_message_line(&line) {
+            count += 1;
+        }
+        line.clear();
+    }
+
+    Ok(count)
+}
+
+/// The transcript mtime recorded in an index entry: `fileMtime` in epoch
+/// milliseconds, or an RFC 3339 `modified`.
+fn index_time(entry: &Value) -> Option<DateTime<Utc>> {
+    if let Some(ms) = entry.get("fileMtime").and_then(|v| v.as_i64()) {
+        return DateTime::from_timestamp_millis(ms);
+    }
+    entry
+        .get("modified")
+        .and_then(|v| v.as_str())
+        .and_then(|s| s.parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discover_sessions() {
+        let dir = std::env::temp_dir().join(format!("ctx-discover-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("sessions-index.json"),
+            r#"{"entries":[{"sessionId":"gone","messageCount":4},{"sessionId":"both"}]}"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("both.jsonl"),
+            concat!(
+                r#"{"type":"user","message":{"role":"user","content":"hi"}}"#,
+                "\n",
+                r#"{"type":"summary","summary":"s"}"#,
+                "\n",
+                r#"{"type":"assistant","message":{"role":"assistant","content":"ok"}}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+        fs::write(dir.join("new.jsonl"), "").unwrap();
+
+        let sessions = discover_sessions(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let ids: Vec<_> = sessions.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, ["both", "gone", "new"]);
+        assert!(sessions[0].in_index && sessions[0].on_disk);
+        assert_eq!(sessions[0].message_count, 2);
+        assert!(!sessions[1].on_disk);
+        assert_eq!(sessions[1].message_count, 4);
+        assert!(!sessions[2].in_index);
+    }
+
+    #[test]
+    fn test_count_matches_full_parse() {
+        let path = std::env::temp_dir().join(format!("ctx-count-{}.jsonl", std::process::id()));
+        let lines = [
+            // A nested `"type":"user"` inside tool input is not an entry
+            r#"{"type":"assistant","uuid":"a","message":{"role":"assistant","content":[{"type":"tool_use","id":"t","name":"Write","input":{"content":"{\"type\":\"user\"}"}}]}}"#,
+            r#"{"type":"progress","data":{"type":"user"}}"#,
+            r#"{"type":"user"}"#,
+            r#"{"type":"message","role":"user","content":"legacy"}"#,
+            "not json \"user\"",
+        ];
+        fs::write(&path, lines.join("\n")).unwrap();
+
+        let counted = count_messages(&path).unwrap();
+        let parsed = crate::parsers::parse_session_jsonl("s", "p", &path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(counted, 2);
+        assert_eq!(counted, parsed.messages.len());
+    }
+}
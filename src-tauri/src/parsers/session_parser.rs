@@ -1,82 +1,639 @@
-use crate::models::{Message, MessageContent, Session};
+use crate::models::usage::usage_by_model;
+use crate::models::{
+    ContentBlock, DiagnosticKind, Message, MessageContent, ParseDiagnostic, Session,
+    SessionDelta, ShellOutput, TailCursor, TokenUsage,
+};
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
 use std::path::Path;
 
+const SNIPPET_LEN: usize = 120;
+const TITLE_CHARS: usize = 80;
+/// How much of a file `session_time_range` looks at for the first and last timestamps.
+const HEAD_LINES: usize = 50;
+const TAIL_BYTES: u64 = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    /// Fail on the first malformed line instead of recording a diagnostic.
+    pub strict: bool,
+}
+
+/// What a single JSONL line contributes to a session.
+enum ParsedLine {
+    Message(Box<Message>),
+    /// A non-message entry that still takes part in the `parentUuid` chain.
+    Link {
+        uuid: String,
+        parent_uuid: Option<String>,
+    },
+    Summary(String),
+    Waiting,
+    Other,
+}
+
+struct LineError {
+    kind: DiagnosticKind,
+    message: String,
+}
+
+impl LineError {
+    fn new(kind: DiagnosticKind, message: impl Into<String>) -> Self {
+        LineError {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+/// Accumulates parsed lines; shared by full and incremental parsing.
+struct LineCollector {
+    options: ParseOptions,
+    first_index: usize,
+    messages: Vec<Message>,
+    links: HashMap<String, Option<String>>,
+    is_waiting: bool,
+    summary: Option<String>,
+    diagnostics: Vec<ParseDiagnostic>,
+}
+
+impl LineCollector {
+    fn new(options: ParseOptions, first_index: usize) -> Self {
+        LineCollector {
+            options,
+            first_index,
+            messages: Vec::new(),
+            links: HashMap::new(),
+            is_waiting: false,
+            summary: None,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Handles one raw line (without its newline). `line` is 1-based.
+    fn push(&mut self, raw: &[u8], line: usize, byte_offset: u64) -> Result<()> {
+        let parsed = match std::str::from_utf8(raw) {
+            Ok(text) if text.trim().is_empty() => return Ok(()),
+            Ok(text) => parse_line(text, self.first_index + self.messages.len()),
+            Err(e) => Err(LineError::new(DiagnosticKind::InvalidUtf8, e.to_string())),
+        };
+
+        match parsed {
+            Ok(ParsedLine::Message(msg)) => self.messages.push(relink(*msg, &self.links)),
+            Ok(ParsedLine::Link { uuid, parent_uuid }) => {
+                self.links.insert(uuid, parent_uuid);
+            }
+            Ok(ParsedLine::Summary(summary)) => self.summary = Some(summary),
+            Ok(ParsedLine::Waiting) => self.is_waiting = true,
+            Ok(ParsedLine::Other) => {}
+            Err(err) => {
+                let diagnostic = ParseDiagnostic {
+                    line,
+                    byte_offset,
+                    kind: err.kind,
+                    message: err.message,
+                    snippet: snippet(raw),
+                };
+                if self.options.strict {
+                    anyhow::bail!(
+                        "line {} (byte {}): {}",
+                        diagnostic.line,
+                        diagnostic.byte_offset,
+                        diagnostic.message
+                    );
+                }
+                self.diagnostics.push(diagnostic);
+            }
+        }
+
+        Ok(())
+    }
+}
+
 pub fn parse_session_jsonl(
     session_id: &str,
     project_id: &str,
     file_path: &Path,
+) -> Result<Session> {
+    parse_session_jsonl_with(session_id, project_id, file_path, ParseOptions::default())
+}
+
+/// Parses a whole session file. Malformed lines are skipped and reported in
+/// `Session::diagnostics`, or abort the parse in strict mode.
+pub fn parse_session_jsonl_with(
+    session_id: &str,
+    project_id: &str,
+    file_path: &Path,
+    options: ParseOptions,
 ) -> Result<Session> {
     let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
+    let mut reader = BufReader::new(file);
 
-    let mut messages = Vec::new();
-    let mut is_waiting = false;
+    let mut collector = LineCollector::new(options, 0);
+    let mut buf = Vec::new();
+    let mut offset = 0u64;
+    let mut line = 0;
 
-    for line in reader.lines() {
-        let line = line?;
-        if line.trim().is_empty() {
-            continue;
+    loop {
+        buf.clear();
+        let read = reader.read_until(b'\n', &mut buf)?;
+        if read == 0 {
+            break;
         }
-
-        match serde_json::from_str::<serde_json::Value>(&line) {
-            Ok(json) => {
-                // Check if this is a message-type entry
-                if let Some(entry_type) = json.get("type").and_then(|v| v.as_str()) {
-                    match entry_type {
-                        "message" => {
-                            if let (Some(role), Some(content)) =
-                                (json.get("role"), json.get("content"))
-                            {
-                                let role = role.as_str().unwrap_or("unknown").to_string();
-                                let content = MessageContent::Object(content.clone());
-                                let timestamp = json
-                                    .get("timestamp")
-                                    .and_then(|v| v.as_str())
-                                    .unwrap_or("")
-                                    .to_string();
-
-                                let msg = Message {
-                                    id: format!("msg_{}", messages.len()),
-                                    role,
-                                    content,
-                                    timestamp,
-                                };
-                                messages.push(msg);
-                            }
-                        }
-                        "user_input_required" | "waiting" => {
-                            is_waiting = true;
-                        }
-                        _ => {}
-                    }
-                }
-            }
-            Err(_) => {
-                // Skip malformed JSON lines
-                continue;
-            }
+        line += 1;
+        if buf.last() == Some(&b'\n') {
+            buf.pop();
         }
+        collector.push(&buf, line, offset)?;
+        offset += read as u64;
     }
 
-    let now = chrono::Utc::now().to_rfc3339();
+    let LineCollector {
+        messages,
+        is_waiting,
+        summary,
+        diagnostics,
+        ..
+    } = collector;
+
+    let name =
+        derive_title(summary.as_deref(), &messages).unwrap_or_else(|| session_id.to_string());
+
+    // Session-level metadata comes from the entries themselves: the first cwd
+    // seen, and the latest branch/version (both can change mid-session).
+    let cwd = messages.iter().find_map(|m| m.cwd.clone());
+    let git_branch = messages.iter().rev().find_map(|m| m.git_branch.clone());
+    let version = messages.iter().rev().find_map(|m| m.version.clone());
+
+    let mut usage = TokenUsage::default();
+    for model_usage in usage_by_model(&messages).values() {
+        usage.add(model_usage);
+    }
+
+    let first = messages.iter().filter_map(|m| m.timestamp).min();
+    let last = messages.iter().filter_map(|m| m.timestamp).max();
+    let (created_at, updated_at) = match (first, last) {
+        (Some(first), Some(last)) => (first, last),
+        _ => file_times(file_path)?,
+    };
 
     Ok(Session {
         id: session_id.to_string(),
         project_id: project_id.to_string(),
-        name: session_id.to_string(),
+        name,
+        summary,
         messages,
-        created_at: now.clone(),
-        updated_at: now,
+        created_at,
+        updated_at,
         is_waiting,
+        cwd,
+        git_branch,
+        version,
+        usage,
+        diagnostics,
     })
 }
 
+/// Parses only what was appended to `file_path` since `cursor`.
+///
+/// A trailing line without a newline is treated as still being written and is
+/// carried over to the next call instead of being parsed. If the file is now
+/// shorter than the cursor offset it was rewritten, so it is re-read from the
+/// start and the delta is flagged as a reset. So is a cursor carrying more
+/// bytes than it claims to have read, which cannot have come from this file.
+pub fn parse_session_tail(file_path: &Path, cursor: &TailCursor) -> Result<SessionDelta> {
+    parse_session_range(file_path, cursor, None)
+}
+
+/// A cursor positioned at byte `offset` of `file_path`, as if the file had
+/// been tailed up to there, so that following it does not replay history.
+pub fn tail_cursor_at(file_path: &Path, offset: u64) -> Result<TailCursor> {
+    Ok(parse_session_range(file_path, &TailCursor::default(), Some(offset))?.cursor)
+}
+
+fn parse_session_range(
+    file_path: &Path,
+    cursor: &TailCursor,
+    end: Option<u64>,
+) -> Result<SessionDelta> {
+    let mut file = File::open(file_path)?;
+    let file_len = file.metadata()?.len();
+    let len = end.map_or(file_len, |end| end.min(file_len));
+
+    // The carried bytes were read before `cursor.offset`
+    let carried_from = cursor.offset.checked_sub(cursor.carry.len() as u64);
+    let reset = len < cursor.offset || carried_from.is_none();
+    let (start, mut offset, mut buf, message_count, line_count, links) = match carried_from {
+        Some(carried_from) if !reset => (
+            cursor.offset,
+            carried_from,
+            cursor.carry.clone(),
+            cursor.message_count,
+            cursor.line_count,
+            cursor.links.clone(),
+        ),
+        _ => (0, 0, Vec::new(), 0, 0, HashMap::new()),
+    };
+
+    file.seek(SeekFrom::Start(start))?;
+    file.take(len - start).read_to_end(&mut buf)?;
+
+    let complete = buf.iter().rposition(|&b| b == b'\n').map_or(0, |i| i + 1);
+    let carry = buf.split_off(complete);
+
+    let mut collector = LineCollector::new(ParseOptions::default(), message_count);
+    collector.links = links;
+    let mut line = line_count;
+
+    if let Some((_, lines)) = buf.split_last() {
+        for raw in lines.split(|&b| b == b'\n') {
+            line += 1;
+            collector.push(raw, line, offset)?;
+            offset += raw.len() as u64 + 1;
+        }
+    }
+
+    let message_count = message_count + collector.messages.len();
+
+    Ok(SessionDelta {
+        messages: collector.messages,
+        is_waiting: collector.is_waiting,
+        reset,
+        diagnostics: collector.diagnostics,
+        cursor: TailCursor {
+            offset: len,
+            carry,
+            message_count,
+            line_count: line,
+            links: collector.links,
+        },
+    })
+}
+
+/// Picks a display title: the transcript summary, else the first prompt the
+/// user actually typed in the main conversation.
+pub fn derive_title(summary: Option<&str>, messages: &[Message]) -> Option<String> {
+    if let Some(summary) = summary.and_then(title_line) {
+        return Some(summary);
+    }
+
+    messages
+        .iter()
+        .filter(|m| m.role == "user" && !m.is_meta && !m.is_sidechain)
+        .find_map(|m| prompt_title(&m.content.text()))
+}
+
+/// Reads just enough of a session file to title it, without building the
+/// full message list.
+pub fn read_session_title(file_path: &Path) -> Result<Option<String>> {
+    let reader = BufReader::new(File::open(file_path)?);
+    let mut summary = None;
+    let mut first_prompt = None;
+
+    for line in reader.lines().map_while(|line| line.ok()) {
+        // Cheap substring checks avoid parsing every line as JSON
+        let maybe_summary = line.contains("\"summary\"");
+        let maybe_prompt = first_prompt.is_none() && line.contains("\"user\"");
+        if !maybe_summary && !maybe_prompt {
+            continue;
+        }
+
+        match parse_line(&line, 0) {
+            Ok(ParsedLine::Summary(text)) => summary = Some(text),
+            Ok(ParsedLine::Message(msg))
+                if first_prompt.is_none()
+                    && msg.role == "user"
+                    && !msg.is_meta
+                    && !msg.is_sidechain =>
+            {
+                first_prompt = prompt_title(&msg.content.text());
+            }
+            _ => {}
+        }
+    }
+
+    Ok(summary.as_deref().and_then(title_line).or(first_prompt))
+}
+
+/// Title from a user prompt, skipping command wrappers and injected output.
+fn prompt_title(text: &str) -> Option<String> {
+    let title = title_line(text)?;
+    if title.starts_with('<') || title.starts_with("Caveat:") {
+        return None;
+    }
+    Some(title)
+}
+
+/// First non-empty line of `text`, shortened to `TITLE_CHARS`.
+fn title_line(text: &str) -> Option<String> {
+    let line = text.lines().map(str::trim).find(|l| !l.is_empty())?;
+    match line.char_indices().nth(TITLE_CHARS) {
+        Some((end, _)) => Some(format!("{}…", line[..end].trim_end())),
+        None => Some(line.to_string()),
+    }
+}
+
+/// Finds a session's first and last entry timestamps by reading only the head
+/// and tail of the file, falling back to the file's own times.
+pub fn session_time_range(file_path: &Path) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut file = File::open(file_path)?;
+    let len = file.metadata()?.len();
+
+    let first = BufReader::new(&mut file)
+        .lines()
+        .take(HEAD_LINES)
+        .map_while(|line| line.ok())
+        .find_map(|line| line_timestamp(&line));
+
+    file.seek(SeekFrom::Start(len.saturating_sub(TAIL_BYTES)))?;
+    let mut tail = Vec::new();
+    file.read_to_end(&mut tail)?;
+    let last = tail
+        .split(|&b| b == b'\n')
+        .rev()
+        .filter_map(|line| std::str::from_utf8(line).ok())
+        .find_map(line_timestamp);
+
+    match (first, last) {
+        (Some(first), Some(last)) => Ok((first.min(last), first.max(last))),
+        _ => file_times(file_path),
+    }
+}
+
+/// Creation and modification time of a file, for sessions without timestamps.
+pub fn file_times(path: &Path) -> Result<(DateTime<Utc>, DateTime<Utc>)> {
+    let metadata = std::fs::metadata(path)?;
+    let modified: DateTime<Utc> = metadata.modified()?.into();
+    let created = metadata
+        .created()
+        .map(DateTime::<Utc>::from)
+        .unwrap_or(modified);
+
+    Ok((created.min(modified), modified))
+}
+
+fn line_timestamp(line: &str) -> Option<DateTime<Utc>> {
+    serde_json::from_str::<Value>(line)
+        .ok()
+        .as_ref()
+        .and_then(parse_timestamp)
+}
+
+fn parse_timestamp(json: &Value) -> Option<DateTime<Utc>> {
+    json.get("timestamp")
+        .and_then(|v| v.as_str())
+        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Whether a raw transcript line becomes a message in a full parse, without
+/// keeping the message.
+pub(crate) fn is_message_line(raw: &[u8]) -> bool {
+    let Ok(line) = std::str::from_utf8(raw) else {
+        return false;
+    };
+    // Cheap substring checks avoid parsing lines that cannot be messages
+    let maybe_message = ["\"user\"", "\"assistant\"", "\"message\""]
+        .iter()
+        .any(|kind| line.contains(kind));
+    maybe_message && matches!(parse_line(line, 0), Ok(ParsedLine::Message(_)))
+}
+
+fn parse_line(line: &str, index: usize) -> Result<ParsedLine, LineError> {
+    let json = serde_json::from_str::<Value>(line).map_err(|e| {
+        let kind = if e.is_eof() {
+            DiagnosticKind::Truncated
+        } else {
+            DiagnosticKind::InvalidJson
+        };
+        LineError::new(kind, e.to_string())
+    })?;
+
+    if !json.is_object() {
+        return Err(LineError::new(
+            DiagnosticKind::NotAnObject,
+            "entry is not a JSON object",
+        ));
+    }
+
+    // Check if this is a message-type entry
+    let parsed = match json.get("type").and_then(|v| v.as_str()) {
+        // Claude Code transcripts: the message lives under a nested `message`
+        Some("user") | Some("assistant") => parse_transcript_entry(&json, index)
+            .map(|msg| ParsedLine::Message(Box::new(msg)))
+            .ok_or_else(|| {
+                LineError::new(DiagnosticKind::MissingField, "entry has no message content")
+            })?,
+        // Legacy shape with top-level `role`/`content`
+        Some("message") => parse_legacy_entry(&json, index)
+            .map(|msg| ParsedLine::Message(Box::new(msg)))
+            .ok_or_else(|| {
+                LineError::new(DiagnosticKind::MissingField, "message has no role or content")
+            })?,
+        Some("summary") => match str_field(&json, "summary") {
+            Some(summary) => ParsedLine::Summary(summary),
+            None => ParsedLine::Other,
+        },
+        Some("user_input_required") | Some("waiting") => ParsedLine::Waiting,
+        _ => match str_field(&json, "uuid") {
+            Some(uuid) => ParsedLine::Link {
+                uuid,
+                parent_uuid: str_field(&json, "parentUuid"),
+            },
+            None => ParsedLine::Other,
+        },
+    };
+
+    Ok(parsed)
+}
+
+fn snippet(raw: &[u8]) -> String {
+    let text = String::from_utf8_lossy(raw);
+    match text.char_indices().nth(SNIPPET_LEN) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.into_owned(),
+    }
+}
+
+/// Points a message's `parent_uuid` at its nearest message ancestor, skipping
+/// over non-message entries (system notices, attachments, ...) in between.
+fn relink(mut msg: Message, links: &HashMap<String, Option<String>>) -> Message {
+    let mut hops = 0;
+    while let Some(parent) = msg.parent_uuid.as_ref().and_then(|p| links.get(p)) {
+        msg.parent_uuid = parent.clone();
+        hops += 1;
+        if hops > links.len() {
+            break;
+        }
+    }
+    msg
+}
+
+/// Parses a `"type": "user"` / `"type": "assistant"` transcript entry.
+pub(crate) fn parse_transcript_entry(json: &Value, index: usize) -> Option<Message> {
+    let message = json.get("message")?;
+    let role = message
+        .get("role")
+        .and_then(|v| v.as_str())
+        .or_else(|| json.get("type").and_then(|v| v.as_str()))
+        .unwrap_or("unknown")
+        .to_string();
+    let content = parse_content(message.get("content")?);
+    let uuid = str_field(json, "uuid");
+
+    Some(Message {
+        id: uuid.clone().unwrap_or_else(|| format!("msg_{}", index)),
+        role,
+        content,
+        timestamp: parse_timestamp(json),
+        uuid,
+        parent_uuid: str_field(json, "parentUuid"),
+        session_id: str_field(json, "sessionId"),
+        cwd: str_field(json, "cwd"),
+        git_branch: str_field(json, "gitBranch"),
+        version: str_field(json, "version"),
+        is_sidechain: json
+            .get("isSidechain")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        is_meta: json
+            .get("isMeta")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+        model: str_field(message, "model"),
+        usage: message.get("usage").map(parse_usage),
+        api_message_id: str_field(message, "id"),
+        shell_output: json.get("toolUseResult").and_then(parse_shell_output),
+    })
+}
+
+/// Bash results carry their streams in `toolUseResult`; failed commands
+/// record a plain error string there instead.
+fn parse_shell_output(result: &Value) -> Option<ShellOutput> {
+    let stream = |key: &str| result.get(key).and_then(|v| v.as_str()).map(String::from);
+
+    Some(ShellOutput {
+        stdout: stream("stdout")?,
+        stderr: stream("stderr").unwrap_or_default(),
+        interrupted: result
+            .get("interrupted")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    })
+}
+
+/// Parses the legacy `"type": "message"` shape with top-level `role`/`content`.
+fn parse_legacy_entry(json: &Value, index: usize) -> Option<Message> {
+    let (role, content) = (json.get("role")?, json.get("content")?);
+
+    Some(Message {
+        id: format!("msg_{}", index),
+        role: role.as_str().unwrap_or("unknown").to_string(),
+        content: parse_content(content),
+        timestamp: parse_timestamp(json),
+        uuid: None,
+        parent_uuid: None,
+        session_id: None,
+        cwd: None,
+        git_branch: None,
+        version: None,
+        is_sidechain: false,
+        is_meta: false,
+        model: None,
+        usage: None,
+        api_message_id: None,
+        shell_output: None,
+    })
+}
+
+pub(crate) fn parse_usage(usage: &Value) -> TokenUsage {
+    let count = |key: &str| usage.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+
+    TokenUsage {
+        input_tokens: count("input_tokens"),
+        output_tokens: count("output_tokens"),
+        cache_creation_input_tokens: count("cache_creation_input_tokens"),
+        cache_read_input_tokens: count("cache_read_input_tokens"),
+    }
+}
+
+/// Converts a raw `content` value (string or block array) into typed content.
+pub fn parse_content(content: &Value) -> MessageContent {
+    match content {
+        Value::String(text) => MessageContent::Text(text.clone()),
+        Value::Array(blocks) => MessageContent::Blocks(blocks.iter().map(parse_block).collect()),
+        other => MessageContent::Blocks(vec![parse_block(other)]),
+    }
+}
+
+fn parse_block(block: &Value) -> ContentBlock {
+    let unknown = || ContentBlock::Unknown { raw: block.clone() };
+
+    match block.get("type").and_then(|v| v.as_str()) {
+        Some("text") => match block.get("text").and_then(|v| v.as_str()) {
+            Some(text) => ContentBlock::Text { text: text.to_string() },
+            None => unknown(),
+        },
+        Some("thinking") => ContentBlock::Thinking {
+            thinking: str_field(block, "thinking").unwrap_or_default(),
+        },
+        Some("tool_use") => match (str_field(block, "id"), str_field(block, "name")) {
+            (Some(id), Some(name)) => ContentBlock::ToolUse {
+                id,
+                name,
+                input: block.get("input").cloned().unwrap_or(Value::Null),
+            },
+            _ => unknown(),
+        },
+        Some("tool_result") => match str_field(block, "tool_use_id") {
+            Some(tool_use_id) => ContentBlock::ToolResult {
+                tool_use_id,
+                is_error: block
+                    .get("is_error")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                content: match block.get("content") {
+                    Some(Value::String(text)) => vec![ContentBlock::Text { text: text.clone() }],
+                    Some(Value::Array(items)) => items.iter().map(parse_block).collect(),
+                    _ => Vec::new(),
+                },
+                original_size: None,
+            },
+            None => unknown(),
+        },
+        Some("image") => {
+            let source = block.get("source").unwrap_or(&Value::Null);
+            ContentBlock::Image {
+                media_type: str_field(source, "media_type"),
+                data: str_field(source, "data"),
+                url: str_field(source, "url"),
+            }
+        }
+        _ => unknown(),
+    }
+}
+
+fn str_field(json: &Value, key: &str) -> Option<String> {
+    json.get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::PathBuf;
+
+    fn write_fixture(name: &str, lines: &[&str]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("ctx_{}_{}.jsonl", name, std::process::id()));
+        std::fs::write(&path, lines.join("\n")).unwrap();
+        path
+    }
 
     #[test]
     fn test_parse_session_jsonl() {
@@ -84,4 +641,237 @@ mod tests {
         let result = parse_session_jsonl("test_session", "test_project", Path::new("non_existent"));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_parse_claude_code_transcript() {
+        let path = write_fixture(
+            "transcript",
+            &[
+                r#"{"type":"summary","summary":"Fix login","leafUuid":"b"}"#,
+                r#"{"type":"user","uuid":"a","parentUuid":null,"sessionId":"s1","cwd":"/home/alice/api","gitBranch":"main","version":"1.0.0","timestamp":"2025-01-01T00:00:00Z","message":{"role":"user","content":"hello"}}"#,
+                r#"{"type":"system","uuid":"sys","parentUuid":"a","content":"hook ran"}"#,
+                r#"{"type":"assistant","uuid":"b","parentUuid":"sys","sessionId":"s1","cwd":"/home/alice/api","gitBranch":"feature","version":"1.0.1","timestamp":"2025-01-01T00:00:05Z","message":{"role":"assistant","content":[{"type":"text","text":"hi"}]}}"#,
+                r#"{"type":"message","role":"user","content":"legacy"}"#,
+            ],
+        );
+
+        let session = parse_session_jsonl("s1", "p1", &path).unwrap();
+        let range = session_time_range(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(session.messages.len(), 3);
+        assert_eq!(session.messages[0].id, "a");
+        assert!(matches!(session.messages[0].content, MessageContent::Text(ref t) if t == "hello"));
+        assert_eq!(session.messages[1].parent_uuid.as_deref(), Some("a"));
+        assert_eq!(session.messages[1].role, "assistant");
+        assert_eq!(session.messages[1].content.text(), "hi");
+        assert_eq!(session.messages[2].id, "msg_2");
+        assert_eq!(session.cwd.as_deref(), Some("/home/alice/api"));
+        assert_eq!(session.git_branch.as_deref(), Some("feature"));
+        assert_eq!(session.version.as_deref(), Some("1.0.1"));
+        assert_eq!(session.created_at.to_rfc3339(), "2025-01-01T00:00:00+00:00");
+        assert_eq!(session.updated_at.to_rfc3339(), "2025-01-01T00:00:05+00:00");
+        assert_eq!(range, (session.created_at, session.updated_at));
+        assert_eq!(session.summary.as_deref(), Some("Fix login"));
+        assert_eq!(session.name, "Fix login");
+    }
+
+    #[test]
+    fn test_parse_shell_output() {
+        let entry = serde_json::json!({
+            "type": "user",
+            "message": {"role": "user", "content": [{"type": "tool_result", "tool_use_id": "t1", "content": "ok"}]},
+            "toolUseResult": {"stdout": "ok", "stderr": "warning: x", "interrupted": false},
+        });
+        let failed = serde_json::json!({
+            "type": "user",
+            "message": {"role": "user", "content": "x"},
+            "toolUseResult": "Error: Exit code 1",
+        });
+
+        let message = parse_transcript_entry(&entry, 0).unwrap();
+
+        assert_eq!(
+            message.shell_output,
+            Some(ShellOutput {
+                stdout: "ok".to_string(),
+                stderr: "warning: x".to_string(),
+                interrupted: false,
+            })
+        );
+        assert_eq!(
+            parse_transcript_entry(&failed, 0).unwrap().shell_output,
+            None
+        );
+    }
+
+    #[test]
+    fn test_title_from_first_prompt() {
+        let path = write_fixture(
+            "title",
+            &[
+                r#"{"type":"user","uuid":"a","isMeta":true,"message":{"role":"user","content":"Caveat: the messages below were generated by the user while running local commands."}}"#,
+                r#"{"type":"user","uuid":"b","message":{"role":"user","content":"<command-name>/clear</command-name>"}}"#,
+                r#"{"type":"user","uuid":"c","message":{"role":"user","content":[{"type":"text","text":"\n  Add retries to the HTTP client\nand tests"}]}}"#,
+            ],
+        );
+
+        let session = parse_session_jsonl("s", "p", &path).unwrap();
+        let quick = read_session_title(&path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(session.summary, None);
+        assert_eq!(session.name, "Add retries to the HTTP client");
+        assert_eq!(quick.as_deref(), Some("Add retries to the HTTP client"));
+    }
+
+    #[test]
+    fn test_session_usage_counts_each_response_once() {
+        let path = write_fixture(
+            "usage",
+            &[
+                r#"{"type":"assistant","uuid":"a","message":{"id":"msg_1","model":"claude-sonnet-4-5","role":"assistant","content":[{"type":"thinking","thinking":"..."}],"usage":{"input_tokens":10,"output_tokens":1,"cache_read_input_tokens":100}}}"#,
+                r#"{"type":"assistant","uuid":"b","parentUuid":"a","message":{"id":"msg_1","model":"claude-sonnet-4-5","role":"assistant","content":[{"type":"text","text":"done"}],"usage":{"input_tokens":10,"output_tokens":20,"cache_read_input_tokens":100}}}"#,
+                r#"{"type":"assistant","uuid":"c","parentUuid":"b","message":{"id":"msg_2","model":"claude-sonnet-4-5","role":"assistant","content":"again","usage":{"input_tokens":5,"output_tokens":5,"cache_creation_input_tokens":50}}}"#,
+            ],
+        );
+
+        let session = parse_session_jsonl("s", "p", &path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(session.messages[0].model.as_deref(), Some("claude-sonnet-4-5"));
+        assert_eq!(session.usage.input_tokens, 15);
+        assert_eq!(session.usage.output_tokens, 25);
+        assert_eq!(session.usage.cache_read_input_tokens, 100);
+        assert_eq!(session.usage.cache_creation_input_tokens, 50);
+    }
+
+    #[test]
+    fn test_parse_diagnostics() {
+        let path = write_fixture(
+            "diagnostics",
+            &[
+                r#"{"type":"user","uuid":"a","message":{"role":"user","content":"ok"}}"#,
+                r#"{"type":"user","uuid":"b","message":{"role":"us"#,
+                r#"not json"#,
+                r#"{"type":"assistant","uuid":"c"}"#,
+                r#"[1, 2]"#,
+            ],
+        );
+
+        let session = parse_session_jsonl("s", "p", &path).unwrap();
+        let strict = parse_session_jsonl_with("s", "p", &path, ParseOptions { strict: true });
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(session.messages.len(), 1);
+        let kinds: Vec<_> = session.diagnostics.iter().map(|d| d.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                DiagnosticKind::Truncated,
+                DiagnosticKind::InvalidJson,
+                DiagnosticKind::MissingField,
+                DiagnosticKind::NotAnObject,
+            ]
+        );
+        assert_eq!(session.diagnostics[0].line, 2);
+        assert_eq!(session.diagnostics[0].byte_offset, 68);
+        assert_eq!(session.diagnostics[1].snippet, "not json");
+
+        let err = strict.unwrap_err().to_string();
+        assert!(err.starts_with("line 2 (byte 68)"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_session_tail() {
+        let first = r#"{"type":"user","uuid":"a","message":{"role":"user","content":"one"}}"#;
+        let second = r#"{"type":"assistant","uuid":"b","message":{"role":"assistant","content":"two"}}"#;
+        let path = write_fixture("tail", &[first, ""]);
+
+        let delta = parse_session_tail(&path, &TailCursor::default()).unwrap();
+        assert_eq!(delta.messages.len(), 1);
+        assert!(delta.cursor.carry.is_empty());
+
+        // Half-written line is carried, not parsed
+        let (head, tail) = second.split_at(20);
+        std::fs::write(&path, format!("{}\n{}", first, head)).unwrap();
+        let delta = parse_session_tail(&path, &delta.cursor).unwrap();
+        assert!(delta.messages.is_empty());
+        assert_eq!(delta.cursor.carry, head.as_bytes());
+
+        std::fs::write(&path, format!("{}\n{}{}\n", first, head, tail)).unwrap();
+        let delta = parse_session_tail(&path, &delta.cursor).unwrap();
+        assert_eq!(delta.messages.len(), 1);
+        assert_eq!(delta.messages[0].id, "b");
+        assert_eq!(delta.cursor.message_count, 2);
+
+        // Truncated file starts over
+        std::fs::write(&path, format!("{}\n", first)).unwrap();
+        let delta = parse_session_tail(&path, &delta.cursor).unwrap();
+        std::fs::remove_file(&path).ok();
+        assert!(delta.reset);
+        assert_eq!(delta.messages.len(), 1);
+    }
+
+    #[test]
+    fn test_tail_relinks_across_calls() {
+        let user = r#"{"type":"user","uuid":"a","message":{"role":"user","content":"one"}}"#;
+        let system = r#"{"type":"system","uuid":"sys","parentUuid":"a"}"#;
+        let reply = r#"{"type":"assistant","uuid":"b","parentUuid":"sys","message":{"role":"assistant","content":"two"}}"#;
+        let path = write_fixture("tail_links", &[user, system, ""]);
+
+        let first = parse_session_tail(&path, &TailCursor::default()).unwrap();
+        std::fs::write(&path, [user, system, reply, ""].join("\n")).unwrap();
+        let second = parse_session_tail(&path, &first.cursor).unwrap();
+        let full = parse_session_jsonl("s", "p", &path).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(second.messages[0].parent_uuid.as_deref(), Some("a"));
+        assert_eq!(full.messages[1].parent_uuid, second.messages[0].parent_uuid);
+    }
+
+    #[test]
+    fn test_tail_with_inconsistent_cursor_resets() {
+        let first = r#"{"type":"user","uuid":"a","message":{"role":"user","content":"one"}}"#;
+        let path = write_fixture("tail_cursor", &[first, ""]);
+        let cursor = TailCursor {
+            offset: 2,
+            carry: b"more than two bytes".to_vec(),
+            message_count: 7,
+            line_count: 7,
+            ..Default::default()
+        };
+
+        let delta = parse_session_tail(&path, &cursor).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        assert!(delta.reset);
+        assert_eq!(delta.messages[0].id, "a");
+        assert_eq!(delta.cursor.message_count, 1);
+        assert_eq!(delta.cursor.offset, first.len() as u64 + 1);
+    }
+
+    #[test]
+    fn test_parse_content_blocks() {
+        let content = serde_json::json!([
+            {"type": "thinking", "thinking": "hmm", "signature": "x"},
+            {"type": "tool_use", "id": "toolu_1", "name": "Read", "input": {"file_path": "/a.rs"}},
+            {"type": "tool_result", "tool_use_id": "toolu_1", "is_error": true, "content": "boom"},
+            {"type": "image", "source": {"type": "base64", "media_type": "image/png", "data": "AAA"}},
+            {"type": "server_tool_use", "id": "srv_1"}
+        ]);
+
+        let blocks = match parse_content(&content) {
+            MessageContent::Blocks(blocks) => blocks,
+            other => panic!("expected blocks, got {:?}", other),
+        };
+
+        assert!(matches!(&blocks[0], ContentBlock::Thinking { thinking } if thinking == "hmm"));
+        assert!(matches!(&blocks[1], ContentBlock::ToolUse { name, input, .. }
+            if name == "Read" && input["file_path"] == "/a.rs"));
+        assert!(matches!(&blocks[2], ContentBlock::ToolResult { tool_use_id, is_error: true, content, .. }
+            if tool_use_id == "toolu_1" && content.len() == 1));
+        assert!(matches!(&blocks[3], ContentBlock::Image { media_type: Some(m), .. } if m == "image/png"));
+        assert!(matches!(&blocks[4], ContentBlock::Unknown { .. }));
+    }
 }
@@ -0,0 +1,464 @@
+use crate::diff::{git_file_diff, unified_hunks};
+use crate::models::{
+    ContentBlock, FileEdit, MessageContent, PatchFile, Session, SessionPatch, ToolCallStatus,
+};
+use crate::parsers::extract_tool_calls;
+use crate::parsers::files_touched::resolve_path;
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+/// What is known about a file while replaying the session.
+#[derive(Default)]
+struct FileState {
+    /// Contents before the session's first edit; `None` for created files.
+    original: Option<String>,
+    /// Contents after the latest edit, while they are known.
+    current: Option<String>,
+    is_new: bool,
+    /// A Read of the file was seen, even if its contents were not usable;
+    /// the file existed before the session.
+    read_seen: bool,
+    /// Hunks accumulated once the contents became unknown.
+    approx: String,
+    omitted: bool,
+    edit_count: usize,
+}
+
+/// Replays the session's Edit, MultiEdit and Write calls in order and renders
+/// their net effect as a git patch, alongside a diff for each call.
+///
+/// File contents come from full (un-paged) Read results and from Write calls;
+/// Claude Code refuses to edit a file it has not read, so this usually gives
+/// an exact starting point. When it does not, each edit falls back to a hunk
+/// built from its `old_string`/`new_string` alone, with a warning. Failed
+/// calls are skipped.
+pub fn build_session_patch(session: &Session) -> SessionPatch {
+    let cwd_by_message: HashMap<&str, &str> = session
+        .messages
+        .iter()
+        .filter_map(|m| m.cwd.as_deref().map(|cwd| (m.id.as_str(), cwd)))
+        .collect();
+    let results = result_texts(session);
+
+    let mut files: BTreeMap<String, FileState> = BTreeMap::new();
+    let mut edits = Vec::new();
+    let mut warnings = Vec::new();
+
+    for call in extract_tool_calls(&session.messages) {
+        if call.status == ToolCallStatus::Error {
+            continue;
+        }
+        let Some(raw_path) = call.input.get("file_path").and_then(|v| v.as_str()) else {
+            continue;
+        };
+        let cwd = cwd_by_message.get(call.message_id.as_str()).copied();
+        let path = resolve_path(raw_path, cwd);
+
+        match call.name.as_str() {
+            "Read" => {
+                let paged = call.input.get("offset").is_some() || call.input.get("limit").is_some();
+                let content = results
+                    .get(call.id.as_str())
+                    .filter(|_| !paged)
+                    .and_then(|text| parse_read_result(text));
+                let state = files.entry(path).or_default();
+                state.read_seen = true;
+                if state.edit_count == 0 && content.is_some() {
+                    state.original = content.clone();
+                    state.current = content;
+                }
+            }
+            "Write" => {
+                let Some(content) = call.input.get("content").and_then(|v| v.as_str()) else {
+                    continue;
+                };
+                let state = files.entry(path.clone()).or_default();
+                let exact = match (&state.current, state.edit_count) {
+                    (Some(before), _) => {
+                        push_edit(
+                            &mut edits,
+                            &call,
+                            &path,
+                            unified_hunks(before, content),
+                            true,
+                        );
+                        true
+                    }
+                    // Writing a file that was never read means creating it
+                    (None, 0) if !state.read_seen => {
+                        state.is_new = true;
+                        push_edit(&mut edits, &call, &path, unified_hunks("", content), true);
+                        true
+                    }
+                    (None, _) => {
+                        warnings.push(format!(
+                            "{}: rewritten without a known previous version; left out of the patch",
+                            path
+                        ));
+                        state.omitted = true;
+                        false
+                    }
+                };
+                if exact {
+                    state.current = Some(content.to_string());
+                }
+                state.edit_count += 1;
+            }
+            "Edit" | "MultiEdit" => {
+                let replacements = replacements(&call.name, &call.input);
+                let state = files.entry(path.clone()).or_default();
+                state.edit_count += 1;
+
+                if let Some(before) = state.current.clone() {
+                    match apply_replacements(&before, &replacements) {
+                        Some(after) => {
+                            push_edit(
+                                &mut edits,
+                                &call,
+                                &path,
+                                unified_hunks(&before, &after),
+                                true,
+                            );
+                            state.current = Some(after);
+                            continue;
+                        }
+                        None => {
+                            warnings.push(format!(
+                                "{}: edit {} does not match the replayed contents",
+                                path, call.id
+                            ));
+                            // Keep what was exact so far as ordinary hunks
+                            let original = state.original.as_deref().unwrap_or("");
+                            state.approx = unified_hunks(original, &before);
+                            state.current = None;
+                        }
+                    }
+                }
+
+                warnings.push(format!(
+                    "{}: edit {} made without known contents; its hunk only covers the replaced text",
+                    path, call.id
+                ));
+                let diff: String = replacements
+                    .iter()
+                    .map(|r| unified_hunks(&with_newline(r.old), &with_newline(r.new)))
+                    .collect();
+                state.approx.push_str(&diff);
+                push_edit(&mut edits, &call, &path, diff, false);
+            }
+            _ => {}
+        }
+    }
+
+    let cwd = session.cwd.as_deref();
+    let mut patch = String::new();
+    let mut patch_files = Vec::new();
+
+    for (path, state) in files {
+        if state.edit_count == 0 {
+            continue;
+        }
+        let patch_path = patch_path(&path, cwd);
+        let exact = state.current.is_some() && !state.omitted;
+
+        if !state.omitted {
+            match &state.current {
+                Some(current) => {
+                    let original = if state.is_new {
+                        None
+                    } else {
+                        Some(state.original.as_deref().unwrap_or(""))
+                    };
+                    patch.push_str(&git_file_diff(&patch_path, original, current));
+                }
+                None if !state.approx.is_empty() => {
+                    patch.push_str(&format!(
+                        "diff --git a/{0} b/{0}\n--- a/{0}\n+++ b/{0}\n",
+                        patch_path
+                    ));
+                    patch.push_str(&state.approx);
+                }
+                None => {}
+            }
+        }
+
+        patch_files.push(PatchFile {
+            path,
+            patch_path,
+            is_new: state.is_new,
+            exact,
+            omitted: state.omitted,
+            edit_count: state.edit_count,
+        });
+    }
+
+    SessionPatch {
+        session_id: session.id.clone(),
+        patch,
+        files: patch_files,
+        edits,
+        warnings,
+    }
+}
+
+struct Replacement<'a> {
+    old: &'a str,
+    new: &'a str,
+    replace_all: bool,
+}
+
+fn replacements<'a>(tool: &str, input: &'a Value) -> Vec<Replacement<'a>> {
+    let parse = |edit: &'a Value| {
+        Some(Replacement {
+            old: edit.get("old_string")?.as_str()?,
+            new: edit.get("new_string")?.as_str()?,
+            replace_all: edit
+                .get("replace_all")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        })
+    };
+
+    match tool {
+        "MultiEdit" => input
+            .get("edits")
+            .and_then(|v| v.as_array())
+            .map(|edits| edits.iter().filter_map(parse).collect())
+            .unwrap_or_default(),
+        _ => parse(input).into_iter().collect(),
+    }
+}
+
+/// Applies the replacements in order, or `None` if one of them no longer
+/// matches (the file changed outside the session).
+fn apply_replacements(content: &str, replacements: &[Replacement]) -> Option<String> {
+    let mut content = content.to_string();
+    for r in replacements {
+        if r.old.is_empty() || !content.contains(r.old) {
+            return None;
+        }
+        content = if r.replace_all {
+            content.replace(r.old, r.new)
+        } else {
+            content.replacen(r.old, r.new, 1)
+        };
+    }
+    Some(content)
+}
+
+fn push_edit(
+    edits: &mut Vec<FileEdit>,
+    call: &crate::models::ToolCall,
+    path: &str,
+    diff: String,
+    exact: bool,
+) {
+    edits.push(FileEdit {
+        tool_call_id: call.id.clone(),
+        tool: call.name.clone(),
+        path: path.to_string(),
+        timestamp: call.started_at,
+        diff,
+        exact,
+    });
+}
+
+/// Full result text of every tool call, keyed by `tool_use_id`.
+fn result_texts(session: &Session) -> HashMap<&str, String> {
+    let mut results = HashMap::new();
+    for msg in &session.messages {
+        for block in msg.content.blocks() {
+            if let ContentBlock::ToolResult {
+                tool_use_id,
+                content,
+                ..
+            } = block
+            {
+                results.insert(
+                    tool_use_id.as_str(),
+                    MessageContent::Blocks(content.clone()).text(),
+                );
+            }
+        }
+    }
+    results
+}
+
+/// Recovers file contents from Read's `cat -n` style output (`     1→text`,
+/// or a tab in older versions). Trailing notes such as system reminders are
+/// ignored; the last line is assumed to end with a newline.
+fn parse_read_result(text: &str) -> Option<String> {
+    let mut content = String::new();
+    let mut expected = 1;
+
+    for line in text.lines() {
+        let trimmed = line.trim_start();
+        let rest = trimmed.trim_start_matches(|c: char| c.is_ascii_digit());
+        let Ok(number) = trimmed[..trimmed.len() - rest.len()].parse::<usize>() else {
+            break;
+        };
+        let Some(rest) = rest.strip_prefix('→').or_else(|| rest.strip_prefix('\t')) else {
+            break;
+        };
+        if number != expected {
+            return None;
+        }
+        content.push_str(rest);
+        content.push('\n');
+        expected += 1;
+    }
+
+    (expected > 1).then_some(content)
+}
+
+fn with_newline(text: &str) -> String {
+    if text.ends_with('\n') {
+        text.to_string()
+    } else {
+        format!("{}\n", text)
+    }
+}
+
+/// Paths inside the session's cwd become repository-relative; anything else
+/// keeps its absolute path minus the leading slash.
+fn patch_path(path: &str, cwd: Option<&str>) -> String {
+    cwd.and_then(|cwd| Path::new(path).strip_prefix(cwd).ok())
+        .map(|rel| rel.to_string_lossy().into_owned())
+        .unwrap_or_else(|| path.trim_start_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::test_support::{message, session, tool_result, tool_use};
+    use serde_json::json;
+
+    #[test]
+    fn test_build_session_patch() {
+        let session = session(vec![
+            message(
+                "a",
+                vec![
+                    tool_use("t1", "Read", json!({"file_path": "/repo/src/lib.rs"})),
+                    tool_use(
+                        "t2",
+                        "Edit",
+                        json!({"file_path": "/repo/src/lib.rs", "old_string": "b", "new_string": "B"}),
+                    ),
+                    tool_use(
+                        "t3",
+                        "Write",
+                        json!({"file_path": "notes.md", "content": "hi\n"}),
+                    ),
+                    tool_use(
+                        "t4",
+                        "Edit",
+                        json!({"file_path": "/repo/README.md", "old_string": "x\ny", "new_string": "x\nz"}),
+                    ),
+                ],
+            ),
+            message(
+                "b",
+                vec![
+                    tool_result(
+                        "t1",
+                        false,
+                        "     1→a\n     2→b\n     3→c\n\n<system-reminder>x</system-reminder>",
+                    ),
+                    tool_result("t2", false, "ok"),
+                    tool_result("t3", false, "ok"),
+                    tool_result("t4", false, "ok"),
+                ],
+            ),
+        ]);
+
+        let patch = build_session_patch(&session);
+
+        assert_eq!(
+            patch.patch,
+            "diff --git a/README.md b/README.md\n--- a/README.md\n+++ b/README.md\n\
+             @@ -1,2 +1,2 @@\n x\n-y\n+z\n\
+             diff --git a/notes.md b/notes.md\nnew file mode 100644\n--- /dev/null\n\
+             +++ b/notes.md\n@@ -0,0 +1,1 @@\n+hi\n\
+             diff --git a/src/lib.rs b/src/lib.rs\n--- a/src/lib.rs\n+++ b/src/lib.rs\n\
+             @@ -1,3 +1,3 @@\n a\n-b\n+B\n c\n"
+        );
+        assert_eq!(patch.edits.len(), 3);
+        assert!(patch.edits[0].exact);
+        assert!(!patch.edits[2].exact);
+        let readme = &patch.files[0];
+        assert_eq!(readme.patch_path, "README.md");
+        assert!(!readme.exact && !readme.is_new);
+        assert!(patch.files[1].is_new);
+    }
+
+    #[test]
+    fn test_write_after_partial_read_is_omitted() {
+        let session = session(vec![
+            message(
+                "a",
+                vec![
+                    tool_use(
+                        "t1",
+                        "Read",
+                        json!({"file_path": "/repo/big.txt", "offset": 100, "limit": 10}),
+                    ),
+                    tool_use(
+                        "t2",
+                        "Write",
+                        json!({"file_path": "/repo/big.txt", "content": "new\n"}),
+                    ),
+                ],
+            ),
+            message(
+                "b",
+                vec![
+                    tool_result("t1", false, "   100→x"),
+                    tool_result("t2", false, "ok"),
+                ],
+            ),
+        ]);
+
+        let patch = build_session_patch(&session);
+
+        assert_eq!(patch.patch, "");
+        assert!(patch.files[0].omitted && !patch.files[0].is_new);
+        assert_eq!(patch.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_approximate_hunk_has_no_context() {
+        let session = session(vec![
+            message(
+                "a",
+                vec![tool_use(
+                    "t1",
+                    "Edit",
+                    json!({"file_path": "/repo/notes.txt", "old_string": "two\nthree", "new_string": "2\n3"}),
+                )],
+            ),
+            message("b", vec![tool_result("t1", false, "ok")]),
+        ]);
+
+        let patch = build_session_patch(&session);
+
+        assert!(!patch.files[0].exact);
+        assert_eq!(patch.warnings.len(), 1);
+        // Without context lines, `git apply --unidiff-zero` places the hunk
+        // wherever the replaced lines are
+        assert_eq!(
+            patch.patch,
+            concat!(
+                "diff --git a/notes.txt b/notes.txt\n",
+                "--- a/notes.txt\n",
+                "+++ b/notes.txt\n",
+                "@@ -1,2 +1,2 @@\n",
+                "-two\n",
+                "-three\n",
+                "+2\n",
+                "+3\n",
+            )
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::models::{Session, ShellCommand, ShellOutput, ToolCallStatus};
+use crate::parsers::extract_tool_calls;
+use std::collections::HashMap;
+
+const PREVIEW_CHARS: usize = 200;
+
+/// Extracts every Bash tool call of the session, in execution order.
+///
+/// Stdout, stderr and interruptions come from the `toolUseResult` Claude Code
+/// records with each result. The exit status is not recorded there, so it is
+/// read from the markers Claude Code puts in failed results ("Exit code N",
+/// "Command timed out"); successful runs report exit code 0. Results without
+/// a `toolUseResult` fall back to these markers for interruptions as well.
+pub fn extract_shell_commands(session: &Session) -> Vec<ShellCommand> {
+    let cwd_by_message: HashMap<&str, &str> = session
+        .messages
+        .iter()
+        .filter_map(|m| m.cwd.as_deref().map(|cwd| (m.id.as_str(), cwd)))
+        .collect();
+    let output_by_message: HashMap<&str, &ShellOutput> = session
+        .messages
+        .iter()
+        .filter_map(|m| m.shell_output.as_ref().map(|out| (m.id.as_str(), out)))
+        .collect();
+
+    extract_tool_calls(&session.messages)
+        .into_iter()
+        .filter(|call| call.name == "Bash")
+        .filter_map(|call| {
+            let command = call.input.get("command")?.as_str()?.to_string();
+            let output = call.result_preview.as_deref().unwrap_or("");
+            let shell_output = call
+                .result_message_id
+                .as_deref()
+                .and_then(|id| output_by_message.get(id));
+            let interrupted = match shell_output {
+                Some(shell_output) => shell_output.interrupted,
+                None => {
+                    output.contains("[Request interrupted by user")
+                        || output.contains("Command was aborted")
+                }
+            };
+            let timed_out = output.starts_with("Command timed out");
+            let exit_code = match call.status {
+                ToolCallStatus::Success => Some(0),
+                ToolCallStatus::Error => exit_code(output),
+                ToolCallStatus::Pending => None,
+            };
+
+            Some(ShellCommand {
+                project_id: session.project_id.clone(),
+                session_id: session.id.clone(),
+                description: call
+                    .input
+                    .get("description")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string()),
+                cwd: cwd_by_message
+                    .get(call.message_id.as_str())
+                    .map(|cwd| cwd.to_string()),
+                run_in_background: call
+                    .input
+                    .get("run_in_background")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false),
+                command,
+                status: call.status,
+                exit_code,
+                timed_out,
+                interrupted,
+                output_preview: call.result_preview,
+                stdout_preview: shell_output.map(|out| preview(&out.stdout)),
+                stderr_preview: shell_output.map(|out| preview(&out.stderr)),
+                started_at: call.started_at,
+                duration_ms: call.duration_ms,
+                is_sidechain: call.is_sidechain,
+                tool_call_id: call.id,
+            })
+        })
+        .collect()
+}
+
+fn preview(text: &str) -> String {
+    text.chars().take(PREVIEW_CHARS).collect()
+}
+
+fn exit_code(output: &str) -> Option<i32> {
+    output
+        .strip_prefix("Exit code ")?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ContentBlock, Message};
+    use crate::parsers::test_support::{message, session, tool_result, tool_use};
+
+    fn bash(id: &str, command: &str) -> ContentBlock {
+        tool_use(
+            id,
+            "Bash",
+            serde_json::json!({"command": command, "description": "Run it"}),
+        )
+    }
+
+    #[test]
+    fn test_extract_shell_commands() {
+        let session = session(vec![
+            message(
+                "a",
+                vec![
+                    bash("t1", "cargo test"),
+                    bash("t2", "ls"),
+                    bash("t3", "sleep 1000"),
+                    bash("t4", "make"),
+                ],
+            ),
+            message(
+                "b",
+                vec![
+                    tool_result("t1", true, "Exit code 101\nerror: test failed"),
+                    tool_result("t2", false, "src"),
+                    tool_result("t3", true, "Command timed out after 2m 0.0s"),
+                ],
+            ),
+            Message {
+                shell_output: Some(ShellOutput {
+                    stdout: "built".to_string(),
+                    stderr: "warning: unused".to_string(),
+                    interrupted: true,
+                }),
+                ..message("c", vec![tool_result("t4", false, "built")])
+            },
+        ]);
+
+        let commands = extract_shell_commands(&session);
+
+        assert_eq!(commands.len(), 4);
+        assert_eq!(commands[0].command, "cargo test");
+        assert_eq!(commands[0].exit_code, Some(101));
+        assert_eq!(commands[0].description.as_deref(), Some("Run it"));
+        assert_eq!(commands[0].cwd.as_deref(), Some("/repo"));
+        assert_eq!(commands[1].exit_code, Some(0));
+        assert!(commands[2].timed_out);
+        assert_eq!(commands[2].exit_code, None);
+        assert_eq!(commands[2].stderr_preview, None);
+        assert!(commands[3].interrupted);
+        assert_eq!(commands[3].stdout_preview.as_deref(), Some("built"));
+        assert_eq!(
+            commands[3].stderr_preview.as_deref(),
+            Some("warning: unused")
+        );
+    }
+}
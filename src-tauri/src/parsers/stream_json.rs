@@ -0,0 +1,220 @@
+//! Claude Code's headless protocol: `--output-format stream-json` writes one
+//! JSON event per line, and `--input-format stream-json` reads user messages
+//! the same way.
+
+use super::session_parser::{parse_transcript_entry, parse_usage};
+use super::tool_calls::pair_tool_blocks;
+use crate::models::{Message, TokenUsage, ToolCall};
+use chrono::Utc;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// One line of `stream-json` output.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamEvent {
+    /// The `system`/`init` event that opens every session.
+    Init {
+        session_id: Option<String>,
+        model: Option<String>,
+        cwd: Option<String>,
+        tools: Vec<String>,
+        permission_mode: Option<String>,
+    },
+    /// An assistant message, or a user message carrying tool results.
+    Message { message: Box<Message> },
+    /// The end of a turn.
+    Result(StreamResult),
+    /// Any other event, kept verbatim.
+    Other { raw: Value },
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamResult {
+    /// `success`, or the kind of error such as `error_max_turns`.
+    pub subtype: String,
+    pub is_error: bool,
+    /// The agent's final answer.
+    pub result: Option<String>,
+    pub session_id: Option<String>,
+    /// Cost of the session so far, as computed by Claude Code.
+    pub total_cost_usd: Option<f64>,
+    pub duration_ms: Option<u64>,
+    pub num_turns: Option<u64>,
+    pub usage: Option<TokenUsage>,
+}
+
+/// What a `stream-json` session has done so far, folded from its events.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StreamSummary {
+    /// Claude Code's own session id, naming its transcript.
+    pub claude_session_id: Option<String>,
+    pub model: Option<String>,
+    /// Set from sending a message until its result arrives.
+    pub working: bool,
+    pub tool_calls: Vec<ToolCall>,
+    /// As reported by the latest result.
+    pub cost_usd: Option<f64>,
+    /// The latest result.
+    pub result: Option<StreamResult>,
+    /// Position of each call in `tool_calls` by `tool_use` id.
+    #[serde(skip)]
+    tool_index: HashMap<String, usize>,
+    #[serde(skip)]
+    events: usize,
+}
+
+/// Parses one output line. Returns `None` for anything that is not a JSON
+/// object, which is then treated as raw output.
+pub fn parse_stream_line(line: &str, index: usize) -> Option<StreamEvent> {
+    let json: Value = serde_json::from_str(line).ok()?;
+    if !json.is_object() {
+        return None;
+    }
+    let str_field = |key: &str| json.get(key).and_then(|v| v.as_str()).map(String::from);
+
+    let event = match (
+        json.get("type").and_then(|v| v.as_str()),
+        json.get("subtype").and_then(|v| v.as_str()),
+    ) {
+        (Some("system"), Some("init")) => StreamEvent::Init {
+            session_id: str_field("session_id"),
+            model: str_field("model"),
+            cwd: str_field("cwd"),
+            tools: json
+                .get("tools")
+                .and_then(|v| v.as_array())
+                .map(|tools| {
+                    tools
+                        .iter()
+                        .filter_map(|t| t.as_str().map(String::from))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            permission_mode: str_field("permissionMode"),
+        },
+        (Some("assistant") | Some("user"), _) => match parse_transcript_entry(&json, index) {
+            Some(mut message) => {
+                // Stream messages carry no timestamp; they are happening now
+                message.timestamp.get_or_insert_with(Utc::now);
+                message.session_id = message.session_id.or_else(|| str_field("session_id"));
+                // Messages of a subagent name the Task call that started it
+                message.is_sidechain |= json
+                    .get("parent_tool_use_id")
+                    .is_some_and(|id| !id.is_null());
+                StreamEvent::Message {
+                    message: Box::new(message),
+                }
+            }
+            None => StreamEvent::Other { raw: json },
+        },
+        (Some("result"), subtype) => StreamEvent::Result(StreamResult {
+            subtype: subtype.unwrap_or("success").to_string(),
+            is_error: json
+                .get("is_error")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            result: str_field("result"),
+            session_id: str_field("session_id"),
+            total_cost_usd: json.get("total_cost_usd").and_then(|v| v.as_f64()),
+            duration_ms: json.get("duration_ms").and_then(|v| v.as_u64()),
+            num_turns: json.get("num_turns").and_then(|v| v.as_u64()),
+            usage: json.get("usage").map(parse_usage),
+        }),
+        _ => StreamEvent::Other { raw: json },
+    };
+
+    Some(event)
+}
+
+/// Wraps text typed by the user as a `stream-json` input line.
+pub fn stream_user_message(text: &str) -> String {
+    serde_json::json!({
+        "type": "user",
+        "message": { "role": "user", "content": text },
+    })
+    .to_string()
+}
+
+impl StreamSummary {
+    /// Parses and applies one output line, returning the event it held.
+    pub fn push_line(&mut self, line: &str) -> Option<StreamEvent> {
+        let event = parse_stream_line(line, self.events)?;
+        self.events += 1;
+        self.apply(&event);
+        Some(event)
+    }
+
+    pub fn apply(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::Init {
+                session_id, model, ..
+            } => {
+                self.claude_session_id = session_id.clone();
+                self.model = model.clone();
+                self.working = true;
+            }
+            StreamEvent::Message { message } => {
+                pair_tool_blocks(&mut self.tool_calls, &mut self.tool_index, message);
+                self.working = true;
+            }
+            StreamEvent::Result(result) => {
+                if result.session_id.is_some() {
+                    self.claude_session_id = result.session_id.clone();
+                }
+                self.cost_usd = result.total_cost_usd.or(self.cost_usd);
+                self.result = Some(result.clone());
+                self.working = false;
+            }
+            StreamEvent::Other { .. } => {}
+        }
+    }
+
+    /// Marks a new user message as sent.
+    pub fn start_turn(&mut self) {
+        self.working = true;
+    }
+
+    /// Marks the CLI as exited; a turn cut short by an exit or crash never
+    /// gets its result.
+    pub fn finish(&mut self) {
+        self.working = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ToolCallStatus;
+
+    #[test]
+    fn test_stream_summary() {
+        let lines = [
+            r#"{"type":"system","subtype":"init","session_id":"s1","model":"claude-sonnet-4-5","tools":["Bash"]}"#,
+            r#"{"type":"assistant","session_id":"s1","parent_tool_use_id":null,"message":{"role":"assistant","content":[{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"ls"}}]}}"#,
+            r#"{"type":"user","session_id":"s1","parent_tool_use_id":null,"message":{"role":"user","content":[{"type":"tool_result","tool_use_id":"t1","content":"a.txt"}]}}"#,
+            r#"{"type":"result","subtype":"success","is_error":false,"result":"Done","session_id":"s1","total_cost_usd":0.012}"#,
+            "plain text",
+        ];
+        let mut summary = StreamSummary::default();
+
+        let events: Vec<_> = lines.iter().map(|l| summary.push_line(l)).collect();
+
+        assert!(matches!(events[0], Some(StreamEvent::Init { .. })));
+        assert!(events[4].is_none());
+        assert_eq!(summary.claude_session_id.as_deref(), Some("s1"));
+        assert_eq!(summary.tool_calls.len(), 1);
+        assert_eq!(summary.tool_calls[0].status, ToolCallStatus::Success);
+        assert_eq!(summary.cost_usd, Some(0.012));
+        assert!(!summary.working);
+        assert_eq!(
+            summary.result.as_ref().and_then(|r| r.result.as_deref()),
+            Some("Done")
+        );
+
+        summary.start_turn();
+        summary.finish();
+        assert!(!summary.working);
+    }
+}
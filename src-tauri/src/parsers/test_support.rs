@@ -0,0 +1,64 @@
+//! Transcript fixtures shared by the parser tests.
+
+use crate::models::{ContentBlock, Message, MessageContent, Session};
+use serde_json::Value;
+
+/// An assistant entry in `/repo` whose uuid is its id. Tests override other
+/// fields with struct update syntax.
+pub fn message(id: &str, blocks: Vec<ContentBlock>) -> Message {
+    Message {
+        id: id.to_string(),
+        role: "assistant".to_string(),
+        content: MessageContent::Blocks(blocks),
+        timestamp: None,
+        uuid: Some(id.to_string()),
+        parent_uuid: None,
+        session_id: None,
+        cwd: Some("/repo".to_string()),
+        git_branch: None,
+        version: None,
+        is_sidechain: false,
+        is_meta: false,
+        model: None,
+        usage: None,
+        api_message_id: None,
+        shell_output: None,
+    }
+}
+
+pub fn session(messages: Vec<Message>) -> Session {
+    Session {
+        id: "s".to_string(),
+        project_id: "p".to_string(),
+        name: "s".to_string(),
+        summary: None,
+        messages,
+        created_at: Default::default(),
+        updated_at: Default::default(),
+        is_waiting: false,
+        cwd: Some("/repo".to_string()),
+        git_branch: None,
+        version: None,
+        usage: Default::default(),
+        diagnostics: Vec::new(),
+    }
+}
+
+pub fn tool_use(id: &str, name: &str, input: Value) -> ContentBlock {
+    ContentBlock::ToolUse {
+        id: id.to_string(),
+        name: name.to_string(),
+        input,
+    }
+}
+
+pub fn tool_result(id: &str, is_error: bool, text: &str) -> ContentBlock {
+    ContentBlock::ToolResult {
+        tool_use_id: id.to_string(),
+        is_error,
+        content: vec![ContentBlock::Text {
+            text: text.to_string(),
+        }],
+        original_size: None,
+    }
+}
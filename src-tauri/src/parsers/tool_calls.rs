@@ -0,0 +1,128 @@
+use crate::models::{ContentBlock, Message, MessageContent, ToolCall, ToolCallStatus};
+use std::collections::HashMap;
+
+const PREVIEW_CHARS: usize = 200;
+
+/// Pairs every `tool_use` block with its `tool_result` by `tool_use_id`, in
+/// the order the calls were made. Durations come from the timestamps of the
+/// issuing and answering entries.
+pub fn extract_tool_calls(messages: &[Message]) -> Vec<ToolCall> {
+    let mut calls: Vec<ToolCall> = Vec::new();
+    let mut by_id: HashMap<String, usize> = HashMap::new();
+
+    for msg in messages {
+        pair_tool_blocks(&mut calls, &mut by_id, msg);
+    }
+
+    calls
+}
+
+/// Adds the calls made in `msg` to `calls` and completes the ones it answers.
+/// `by_id` maps each `tool_use` id to its position in `calls`, so a growing
+/// session can be followed one message at a time.
+pub(crate) fn pair_tool_blocks(
+    calls: &mut Vec<ToolCall>,
+    by_id: &mut HashMap<String, usize>,
+    msg: &Message,
+) {
+    for block in msg.content.blocks() {
+        match block {
+            ContentBlock::ToolUse { id, name, input } => {
+                by_id.insert(id.clone(), calls.len());
+                calls.push(ToolCall {
+                    id: id.clone(),
+                    name: name.clone(),
+                    input: input.clone(),
+                    status: ToolCallStatus::Pending,
+                    message_id: msg.id.clone(),
+                    result_message_id: None,
+                    started_at: msg.timestamp,
+                    finished_at: None,
+                    duration_ms: None,
+                    result_preview: None,
+                    is_sidechain: msg.is_sidechain,
+                });
+            }
+            ContentBlock::ToolResult {
+                tool_use_id,
+                is_error,
+                content,
+                ..
+            } => {
+                let Some(call) = by_id.get(tool_use_id).map(|&i| &mut calls[i]) else {
+                    continue;
+                };
+                call.status = if *is_error {
+                    ToolCallStatus::Error
+                } else {
+                    ToolCallStatus::Success
+                };
+                call.result_message_id = Some(msg.id.clone());
+                call.finished_at = msg.timestamp;
+                call.duration_ms = match (call.started_at, msg.timestamp) {
+                    (Some(start), Some(end)) => Some((end - start).num_milliseconds()),
+                    _ => None,
+                };
+                let text = MessageContent::Blocks(content.clone()).text();
+                call.result_preview = Some(text.chars().take(PREVIEW_CHARS).collect());
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parsers::test_support::{message, tool_result, tool_use};
+    use chrono::{DateTime, Utc};
+    use serde_json::json;
+
+    fn at(id: &str, timestamp: &str, blocks: Vec<ContentBlock>) -> Message {
+        Message {
+            timestamp: Some(timestamp.parse::<DateTime<Utc>>().unwrap()),
+            ..message(id, blocks)
+        }
+    }
+
+    #[test]
+    fn test_extract_tool_calls() {
+        let messages = vec![
+            at(
+                "a",
+                "2025-01-01T00:00:00Z",
+                vec![
+                    tool_use("t1", "Bash", json!({})),
+                    tool_use("t2", "Read", json!({})),
+                ],
+            ),
+            at(
+                "b",
+                "2025-01-01T00:00:02.500Z",
+                vec![tool_result("t1", true, "exit 1")],
+            ),
+            at(
+                "c",
+                "2025-01-01T00:00:03Z",
+                vec![tool_result("t2", false, "fn main() {}")],
+            ),
+            at(
+                "d",
+                "2025-01-01T00:00:04Z",
+                vec![tool_use("t3", "Edit", json!({}))],
+            ),
+        ];
+
+        let calls = extract_tool_calls(&messages);
+
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[0].name, "Bash");
+        assert_eq!(calls[0].status, ToolCallStatus::Error);
+        assert_eq!(calls[0].duration_ms, Some(2500));
+        assert_eq!(calls[0].result_message_id.as_deref(), Some("b"));
+        assert_eq!(calls[1].status, ToolCallStatus::Success);
+        assert_eq!(calls[1].result_preview.as_deref(), Some("fn main() {}"));
+        assert_eq!(calls[2].status, ToolCallStatus::Pending);
+        assert_eq!(calls[2].duration_ms, None);
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Values derived from transcripts, kept until the file changes. Transcripts
+/// only grow or get rewritten, so a change of size or mtime is enough to
+/// notice it without reading the file.
+pub struct TranscriptCache<T> {
+    entries: Mutex<BTreeMap<PathBuf, (Stamp, T)>>,
+    limit: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct Stamp {
+    size: u64,
+    modified: Option<SystemTime>,
+}
+
+impl<T: Clone> TranscriptCache<T> {
+    pub const fn new() -> Self {
+        Self::with_limit(usize::MAX)
+    }
+
+    /// A cache holding at most `limit` transcripts, for values too large to
+    /// keep for every session.
+    pub const fn with_limit(limit: usize) -> Self {
+        TranscriptCache {
+            entries: Mutex::new(BTreeMap::new()),
+            limit,
+        }
+    }
+
+    /// The cached value for `path`, computed again if the file changed since.
+    /// Errors are returned, not cached.
+    pub fn get(&self, path: &Path, compute: impl FnOnce(&Path) -> Result<T>) -> Result<T> {
+        // Taken before reading, so a write racing with `compute` only causes
+        // one more recomputation
+        let metadata = fs::metadata(path)?;
+        let stamp = Stamp {
+            size: metadata.len(),
+            modified: metadata.modified().ok(),
+        };
+
+        if let Some((cached, value)) = self.lock().get(path) {
+            if *cached == stamp {
+                return Ok(value.clone());
+            }
+        }
+
+        let value = compute(path)?;
+        let mut entries = self.lock();
+        if entries.len() >= self.limit && !entries.contains_key(path) {
+            entries.pop_first();
+        }
+        entries.insert(path.to_path_buf(), (stamp, value.clone()));
+        Ok(value)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<PathBuf, (Stamp, T)>> {
+        // A panic while holding the lock cannot leave an entry half-written
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recomputes_after_change() {
+        let path = std::env::temp_dir().join(format!("ctx-cache-{}.jsonl", std::process::id()));
+        fs::write(&path, "a\n").unwrap();
+        let cache = TranscriptCache::new();
+        let lines = |p: &Path| Ok(fs::read_to_string(p)?.lines().count());
+
+        let first = cache.get(&path, lines).unwrap();
+        let cached = cache.get(&path, |_| Ok(99)).unwrap();
+        fs::write(&path, "a\nb\n").unwrap();
+        let changed = cache.get(&path, lines).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!((first, cached, changed), (1, 1, 2));
+        assert!(cache.get(&path, lines).is_err());
+    }
+
+    #[test]
+    fn test_limit_evicts() {
+        let dir = std::env::temp_dir().join(format!("ctx-cache-limit-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (a, b) = (dir.join("a.jsonl"), dir.join("b.jsonl"));
+        fs::write(&a, "").unwrap();
+        fs::write(&b, "").unwrap();
+        let cache = TranscriptCache::with_limit(1);
+
+        cache.get(&a, |_| Ok(1)).unwrap();
+        cache.get(&b, |_| Ok(2)).unwrap();
+        let recomputed = cache.get(&a, |_| Ok(3)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(recomputed, 3);
+        assert_eq!(cache.lock().len(), 1);
+    }
+}
@@ -1,16 +1,42 @@
+use crate::models::{LaunchSpec, SessionMode};
+use crate::parsers::project_dir::resolve_working_dir;
+use crate::parsers::stream_json::{stream_user_message, StreamEvent, StreamSummary};
+use crate::roots::{find_project, DEFAULT_ROOT};
 use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex, mpsc};
 use tauri::{AppHandle, Emitter};
 use uuid::Uuid;
-use std::io::Write;
+use std::io::{BufRead, Write};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SessionState {
     Idle,
     Working,
     Waiting,
 }
 
+impl SessionState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SessionState::Idle => "idle",
+            SessionState::Working => "working",
+            SessionState::Waiting => "waiting",
+        }
+    }
+
+    /// The state of a stream-json session: working from a sent message until
+    /// its result, then waiting for the next one.
+    fn of_stream(summary: &StreamSummary) -> Self {
+        if summary.working {
+            SessionState::Working
+        } else if summary.result.is_some() {
+            SessionState::Waiting
+        } else {
+            SessionState::Idle
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct SessionInfo {
     pub id: String,
@@ -18,6 +44,45 @@ pub struct SessionInfo {
     pub state: String, // "idle" | "working" | "waiting"
     pub created_at: String,
     pub output_count: usize,
+    pub resumed_from: Option<TranscriptLink>,
+    pub mode: SessionMode,
+}
+
+/// The historical Claude Code session a live session continues.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TranscriptLink {
+    pub session_id: String,
+    /// Set when the history was forked into a new transcript instead of
+    /// being appended to.
+    pub forked: bool,
+    /// Id of the forked transcript, assigned up front so the live session
+    /// can be found in the history view.
+    pub fork_session_id: Option<String>,
+}
+
+impl TranscriptLink {
+    fn new(session_id: String, fork: bool) -> Self {
+        TranscriptLink {
+            session_id,
+            forked: fork,
+            fork_session_id: fork.then(|| Uuid::new_v4().to_string()),
+        }
+    }
+
+    /// The CLI arguments continuing or forking the transcript, or `None` for
+    /// an id that could be mistaken for a flag or a path.
+    fn resume_args(&self) -> Option<Vec<String>> {
+        let id = &self.session_id;
+        if id.is_empty() || id.starts_with('-') || id.contains(['/', '\\']) {
+            return None;
+        }
+
+        let mut args = vec!["--resume".to_string(), id.clone()];
+        if let Some(fork_id) = &self.fork_session_id {
+            args.extend(["--fork-session", "--session-id", fork_id].map(String::from));
+        }
+        Some(args)
+    }
 }
 
 #[derive(Clone, serde::Serialize)]
@@ -34,6 +99,21 @@ pub struct ManagedProcess {
     pub created_at: String,
     pub output_buffer: Arc<Mutex<VecDeque<OutputLine>>>,
     pub stdin_sender: Option<mpsc::Sender<String>>,
+    pub resumed_from: Option<TranscriptLink>,
+    pub mode: SessionMode,
+    /// Folded `stream-json` events; set in that mode only.
+    pub stream: Option<Arc<Mutex<StreamSummary>>>,
+}
+
+impl ManagedProcess {
+    /// Stream-json sessions derive their state from events; raw ones keep
+    /// the last state set.
+    fn current_state(&self) -> SessionState {
+        match self.stream.as_ref().and_then(|stream| stream.lock().ok()) {
+            Some(summary) => SessionState::of_stream(&summary),
+            None => self.state,
+        }
+    }
 }
 
 pub struct ProcessManager {
@@ -49,12 +129,76 @@ impl ProcessManager {
         }
     }
 
-    pub fn spawn_session(&self, project_id: String) -> Result<String, String> {
+    /// Starts Claude Code in the project's working directory as described by
+    /// `spec`, which is validated first.
+    pub fn spawn_session(&self, project_id: String, spec: LaunchSpec) -> Result<String, String> {
+        self.launch(project_id, spec, None)
+    }
+
+    /// Starts Claude Code on the history of `session_id`, continuing its
+    /// transcript or, with `fork`, branching it into a new one.
+    pub fn resume_session(
+        &self,
+        project_id: String,
+        session_id: String,
+        fork: bool,
+        spec: LaunchSpec,
+    ) -> Result<String, String> {
+        let link = TranscriptLink::new(session_id, fork);
+        self.launch(project_id, spec, Some(link))
+    }
+
+    fn launch(
+        &self,
+        project_id: String,
+        spec: LaunchSpec,
+        resumed_from: Option<TranscriptLink>,
+    ) -> Result<String, String> {
+        spec.validate()?;
+
+        let project = find_project(&project_id).map_err(|e| e.to_string())?;
+        if !project.path.is_dir() {
+            return Err(format!("Project not found: {}", project_id));
+        }
+        let working_dir = resolve_working_dir(&project.path)
+            .filter(|dir| dir.is_dir())
+            .ok_or_else(|| format!("Working directory of {} not found", project_id))?;
+
+        let mut command = std::process::Command::new("claude");
+        command.current_dir(&working_dir);
+        if let Some(link) = &resumed_from {
+            let transcript = project.path.join(format!("{}.jsonl", link.session_id));
+            match link.resume_args() {
+                Some(args) if transcript.is_file() => command.args(args),
+                _ => return Err(format!("Session not found: {}", link.session_id)),
+            };
+        }
+        command.args(spec.args());
+        // Keep the new transcript in the root the project came from. The
+        // default root is the CLI's own, and pointing CLAUDE_CONFIG_DIR at it
+        // would hide the global config and credentials of a plain install.
+        if project.root.name != DEFAULT_ROOT {
+            if let Some(config_dir) = project.root.projects_dir.parent() {
+                command.env("CLAUDE_CONFIG_DIR", config_dir);
+            }
+        }
+        command.envs(&spec.env);
+
         let session_id = Uuid::new_v4().to_string();
 
         let output_buffer = Arc::new(Mutex::new(VecDeque::new()));
         let (stdin_tx, stdin_rx) = mpsc::channel();
 
+        let stream = (spec.mode == SessionMode::StreamJson)
+            .then(|| Arc::new(Mutex::new(StreamSummary::default())));
+        if let (Some(stream), Some(prompt)) = (&stream, &spec.prompt) {
+            // In stream-json mode the CLI reads even its first turn from stdin
+            let _ = stdin_tx.send(stream_user_message(prompt));
+            if let Ok(mut summary) = stream.lock() {
+                summary.start_turn();
+            }
+        }
+
         let process = ManagedProcess {
             id: session_id.clone(),
             project_id: project_id.clone(),
@@ -62,6 +206,9 @@ impl ProcessManager {
             created_at: chrono::Utc::now().to_rfc3339(),
             output_buffer: output_buffer.clone(),
             stdin_sender: Some(stdin_tx),
+            resumed_from: resumed_from.clone(),
+            mode: spec.mode,
+            stream: stream.clone(),
         };
 
         let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
@@ -79,21 +226,23 @@ impl ProcessManager {
             serde_json::json!({
                 "session_id": session_id.clone(),
                 "project_id": project_id.clone(),
+                "resumed_from": resumed_from,
+                "mode": spec.mode,
             }),
         );
 
         // Spawn the actual Claude CLI process in background
         let app_handle = self.app_handle.clone();
         let session_id_clone = session_id.clone();
-        let project_id_clone = project_id.clone();
 
         std::thread::spawn(move || {
             if let Err(e) = Self::run_session(
+                command,
                 session_id_clone,
-                project_id_clone,
                 app_handle,
                 output_buffer,
                 stdin_rx,
+                stream,
             ) {
                 eprintln!("Session error: {}", e);
             }
@@ -129,16 +278,14 @@ impl ProcessManager {
     }
 
     fn run_session(
+        mut command: std::process::Command,
         session_id: String,
-        project_id: String,
         app_handle: AppHandle,
         output_buffer: Arc<Mutex<VecDeque<OutputLine>>>,
         stdin_rx: mpsc::Receiver<String>,
+        stream: Option<Arc<Mutex<StreamSummary>>>,
     ) -> Result<(), String> {
-        // Spawn Claude CLI with project context using standard process
-        let mut command = std::process::Command::new("claude")
-            .arg("--project")
-            .arg(&project_id)
+        let mut command = command
             .stdin(std::process::Stdio::piped())
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
@@ -155,64 +302,56 @@ impl ProcessManager {
         }
 
         // Read stdout
-        if let Some(stdout) = command.stdout.take() {
+        let stdout_thread = command.stdout.take().map(|stdout| {
             let app_handle_clone = app_handle.clone();
             let session_id_clone = session_id.clone();
             let buffer_clone = output_buffer.clone();
+            let stream = stream.clone();
 
             std::thread::spawn(move || {
-                use std::io::BufRead;
                 let reader = std::io::BufReader::new(stdout);
                 let mut last_state = SessionState::Idle;
 
-                for line in reader.lines() {
-                    if let Ok(text) = line {
-                        // Detect state change
-                        let new_state = Self::detect_state(&text);
-                        if !matches!(new_state, SessionState::Idle if matches!(last_state, SessionState::Idle)) {
-                            // Emit state changed event if state differs from idle or changed
-                            let _ = app_handle_clone.emit(
-                                "session-state-changed",
-                                serde_json::json!({
-                                    "session_id": session_id_clone,
-                                    "state": match new_state {
-                                        SessionState::Idle => "idle",
-                                        SessionState::Working => "working",
-                                        SessionState::Waiting => "waiting",
-                                    },
-                                }),
+                for text in lossy_lines(reader) {
+                    if let Some(stream) = &stream {
+                        // Lines that are not events, e.g. a crash message,
+                        // are still shown as they are
+                        if !Self::apply_stream_line(
+                            &app_handle_clone,
+                            &session_id_clone,
+                            &buffer_clone,
+                            stream,
+                            &text,
+                        ) {
+                            Self::record_output(
+                                &app_handle_clone,
+                                &session_id_clone,
+                                &buffer_clone,
+                                text,
+                                "stdout",
                             );
-                            last_state = new_state;
-                        }
-
-                        let output_line = OutputLine {
-                            timestamp: chrono::Utc::now().to_rfc3339(),
-                            text: text.clone(),
-                            line_type: "stdout".to_string(),
-                        };
-
-                        // Add to buffer (keep max 10k lines)
-                        if let Ok(mut buf) = buffer_clone.lock() {
-                            if buf.len() >= 10000 {
-                                buf.pop_front();
-                            }
-                            buf.push_back(output_line.clone());
                         }
+                        continue;
+                    }
 
-                        // Emit to frontend
-                        let _ = app_handle_clone.emit(
-                            "session-output",
-                            serde_json::json!({
-                                "session_id": session_id_clone,
-                                "line": text,
-                                "timestamp": output_line.timestamp,
-                                "type": "stdout",
-                            }),
-                        );
+                    // Detect state change
+                    let new_state = Self::detect_state(&text);
+                    if !(new_state == SessionState::Idle && last_state == SessionState::Idle) {
+                        // Emit state changed event if state differs from idle or changed
+                        Self::emit_state(&app_handle_clone, &session_id_clone, new_state);
+                        last_state = new_state;
                     }
+
+                    Self::record_output(
+                        &app_handle_clone,
+                        &session_id_clone,
+                        &buffer_clone,
+                        text,
+                        "stdout",
+                    );
                 }
-            });
-        }
+            })
+        });
 
         // Read stderr
         if let Some(stderr) = command.stderr.take() {
@@ -221,41 +360,33 @@ impl ProcessManager {
             let buffer_clone = output_buffer.clone();
 
             std::thread::spawn(move || {
-                use std::io::BufRead;
                 let reader = std::io::BufReader::new(stderr);
-                for line in reader.lines() {
-                    if let Ok(text) = line {
-                        let output_line = OutputLine {
-                            timestamp: chrono::Utc::now().to_rfc3339(),
-                            text: text.clone(),
-                            line_type: "stderr".to_string(),
-                        };
-
-                        // Add to buffer
-                        if let Ok(mut buf) = buffer_clone.lock() {
-                            if buf.len() >= 10000 {
-                                buf.pop_front();
-                            }
-                            buf.push_back(output_line.clone());
-                        }
-
-                        // Emit to frontend
-                        let _ = app_handle_clone.emit(
-                            "session-output",
-                            serde_json::json!({
-                                "session_id": session_id_clone,
-                                "line": text,
-                                "timestamp": output_line.timestamp,
-                                "type": "stderr",
-                            }),
-                        );
-                    }
+                for text in lossy_lines(reader) {
+                    Self::record_output(
+                        &app_handle_clone,
+                        &session_id_clone,
+                        &buffer_clone,
+                        text,
+                        "stderr",
+                    );
                 }
             });
         }
 
         // Wait for process to complete
         let status = command.wait().map_err(|e| e.to_string())?;
+        // Let the last events land before the session is marked as ended
+        if let Some(thread) = stdout_thread {
+            let _ = thread.join();
+        }
+        if let Some(stream) = &stream {
+            if let Ok(mut summary) = stream.lock() {
+                if summary.working {
+                    summary.finish();
+                    Self::emit_state(&app_handle, &session_id, SessionState::of_stream(&summary));
+                }
+            }
+        }
 
         // Emit session completion
         let _ = app_handle.emit(
@@ -269,6 +400,104 @@ impl ProcessManager {
         Ok(())
     }
 
+    /// Applies one line of stream-json output and reports what it changed.
+    /// Returns `false` if the line is not an event.
+    fn apply_stream_line(
+        app_handle: &AppHandle,
+        session_id: &str,
+        buffer: &Mutex<VecDeque<OutputLine>>,
+        stream: &Mutex<StreamSummary>,
+        text: &str,
+    ) -> bool {
+        let Ok(mut summary) = stream.lock() else {
+            return false;
+        };
+        let old_state = SessionState::of_stream(&summary);
+        let Some(event) = summary.push_line(text) else {
+            return false;
+        };
+        let new_state = SessionState::of_stream(&summary);
+        let cost_usd = summary.cost_usd;
+        drop(summary);
+
+        let _ = app_handle.emit(
+            "session-stream-event",
+            serde_json::json!({
+                "session_id": session_id,
+                "event": &event,
+            }),
+        );
+        if new_state != old_state {
+            Self::emit_state(app_handle, session_id, new_state);
+        }
+
+        match event {
+            // Keep the plain output readable for stream sessions too
+            StreamEvent::Message { message } if message.role == "assistant" => {
+                let text = message.content.text();
+                if !text.is_empty() {
+                    Self::record_output(app_handle, session_id, buffer, text, "stdout");
+                }
+            }
+            StreamEvent::Result(result) => {
+                let _ = app_handle.emit(
+                    "session-result",
+                    serde_json::json!({
+                        "session_id": session_id,
+                        "result": result,
+                        "cost_usd": cost_usd,
+                    }),
+                );
+            }
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Adds a line to the session's buffer (keeping at most 10k lines) and
+    /// sends it to the frontend.
+    fn record_output(
+        app_handle: &AppHandle,
+        session_id: &str,
+        buffer: &Mutex<VecDeque<OutputLine>>,
+        text: String,
+        line_type: &str,
+    ) {
+        let output_line = OutputLine {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            text,
+            line_type: line_type.to_string(),
+        };
+
+        if let Ok(mut buf) = buffer.lock() {
+            if buf.len() >= 10000 {
+                buf.pop_front();
+            }
+            buf.push_back(output_line.clone());
+        }
+
+        let _ = app_handle.emit(
+            "session-output",
+            serde_json::json!({
+                "session_id": session_id,
+                "line": output_line.text,
+                "timestamp": output_line.timestamp,
+                "type": line_type,
+            }),
+        );
+    }
+
+    fn emit_state(app_handle: &AppHandle, session_id: &str, state: SessionState) {
+        let _ = app_handle.emit(
+            "session-state-changed",
+            serde_json::json!({
+                "session_id": session_id,
+                "state": state.as_str(),
+            }),
+        );
+    }
+
     pub fn terminate_session(&self, session_id: String) -> Result<(), String> {
         let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
 
@@ -301,13 +530,11 @@ impl ProcessManager {
                 SessionInfo {
                     id: p.id.clone(),
                     project_id: p.project_id.clone(),
-                    state: match p.state {
-                        SessionState::Idle => "idle".to_string(),
-                        SessionState::Working => "working".to_string(),
-                        SessionState::Waiting => "waiting".to_string(),
-                    },
+                    state: p.current_state().as_str().to_string(),
                     created_at: p.created_at.clone(),
                     output_count,
+                    resumed_from: p.resumed_from.clone(),
+                    mode: p.mode,
                 }
             })
             .collect();
@@ -330,13 +557,11 @@ impl ProcessManager {
                 SessionInfo {
                     id: p.id.clone(),
                     project_id: p.project_id.clone(),
-                    state: match p.state {
-                        SessionState::Idle => "idle".to_string(),
-                        SessionState::Working => "working".to_string(),
-                        SessionState::Waiting => "waiting".to_string(),
-                    },
+                    state: p.current_state().as_str().to_string(),
                     created_at: p.created_at.clone(),
                     output_count,
+                    resumed_from: p.resumed_from.clone(),
+                    mode: p.mode,
                 }
             })
             .ok_or_else(|| format!("Session not found: {}", session_id))
@@ -350,20 +575,10 @@ impl ProcessManager {
         let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
 
         if let Some(process) = processes.get_mut(session_id) {
-            process.state = new_state.clone();
+            process.state = new_state;
 
             // Emit state changed event
-            let _ = self.app_handle.emit(
-                "session-state-changed",
-                serde_json::json!({
-                    "session_id": session_id,
-                    "state": match new_state {
-                        SessionState::Idle => "idle",
-                        SessionState::Working => "working",
-                        SessionState::Waiting => "waiting",
-                    },
-                }),
-            );
+            Self::emit_state(&self.app_handle, session_id, new_state);
 
             Ok(())
         } else {
@@ -388,16 +603,96 @@ impl ProcessManager {
     pub fn write_to_session(&self, session_id: &str, input: String) -> Result<(), String> {
         let processes = self.processes.lock().map_err(|e| e.to_string())?;
 
-        if let Some(process) = processes.get(session_id) {
-            if let Some(ref stdin_sender) = process.stdin_sender {
-                stdin_sender
-                    .send(input)
-                    .map_err(|e| format!("Failed to send input: {}", e))
-            } else {
-                Err("Session stdin not available".to_string())
+        let process = processes
+            .get(session_id)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        let stdin_sender = process
+            .stdin_sender
+            .as_ref()
+            .ok_or_else(|| "Session stdin not available".to_string())?;
+
+        let Some(stream) = &process.stream else {
+            return stdin_sender
+                .send(input)
+                .map_err(|e| format!("Failed to send input: {}", e));
+        };
+        // Stream-json sessions take each input as a new user turn
+        stdin_sender
+            .send(stream_user_message(&input))
+            .map_err(|e| format!("Failed to send input: {}", e))?;
+        if let Ok(mut summary) = stream.lock() {
+            summary.start_turn();
+        }
+        Self::emit_state(&self.app_handle, session_id, SessionState::Working);
+        Ok(())
+    }
+
+    /// What a stream-json session has done so far.
+    pub fn get_session_stream(&self, session_id: &str) -> Result<StreamSummary, String> {
+        let processes = self.processes.lock().map_err(|e| e.to_string())?;
+
+        let process = processes
+            .get(session_id)
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        let stream = process
+            .stream
+            .as_ref()
+            .ok_or_else(|| format!("Session {} is not in stream-json mode", session_id))?;
+        stream
+            .lock()
+            .map(|summary| summary.clone())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// The lines of `reader` until EOF. Invalid UTF-8 is replaced instead of
+/// ending the stream, so the pipe keeps being drained.
+fn lossy_lines(mut reader: impl BufRead) -> impl Iterator<Item = String> {
+    let mut buf = Vec::new();
+    std::iter::from_fn(move || {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => {
+                if buf.ends_with(b"\n") {
+                    buf.pop();
+                    if buf.ends_with(b"\r") {
+                        buf.pop();
+                    }
+                }
+                Some(String::from_utf8_lossy(&buf).into_owned())
             }
-        } else {
-            Err(format!("Session not found: {}", session_id))
         }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resume_args() {
+        let resume = TranscriptLink::new("abc-123".to_string(), false);
+        assert_eq!(resume.resume_args().unwrap(), ["--resume", "abc-123"]);
+
+        let fork = TranscriptLink::new("abc-123".to_string(), true);
+        let fork_id = fork.fork_session_id.clone().unwrap();
+        assert_eq!(
+            fork.resume_args().unwrap(),
+            ["--resume", "abc-123", "--fork-session", "--session-id", &fork_id]
+        );
+
+        for id in ["", "--help", "../other", "a\\b"] {
+            assert!(TranscriptLink::new(id.to_string(), false).resume_args().is_none());
+        }
+    }
+
+    #[test]
+    fn test_lossy_lines_survive_invalid_utf8() {
+        let input: &[u8] = b"first\n\xff\xfe bad\r\nlast";
+
+        let lines: Vec<_> = lossy_lines(input).collect();
+
+        assert_eq!(lines, ["first", "\u{fffd}\u{fffd} bad", "last"]);
     }
 }
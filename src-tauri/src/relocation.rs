@@ -0,0 +1,488 @@
+//! Moving a project's Claude Code history to follow a relocated repository.
+//!
+//! This is the only place CTX writes under `~/.claude`. Every run is planned
+//! first, backed up to `~/.ctx/backups`, and rolled back from that backup if
+//! any step fails.
+
+use crate::models::{RelocationFile, RelocationPlan};
+use crate::parsers::project_dir::{encode_project_dir, resolve_working_dir};
+use crate::roots::ClaudeRoot;
+use anyhow::{bail, Context, Result};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use walkdir::WalkDir;
+
+/// A transcript written this recently may belong to a running session, which
+/// would keep appending to the old location.
+const RECENT_WRITE: Duration = Duration::from_secs(10);
+
+/// Plans moving the project directory `dir_name` of `root` to the directory
+/// Claude Code would use for `new_working_dir`, counting the `cwd` fields to
+/// rewrite. The project stays in its root.
+pub fn plan_relocation(
+    root: &ClaudeRoot,
+    dir_name: &str,
+    new_working_dir: &Path,
+) -> Result<RelocationPlan> {
+    let project_id = root.project_id(dir_name);
+    let source_dir = root.projects_dir.join(dir_name);
+    if !source_dir.is_dir() {
+        bail!("Project not found: {}", project_id);
+    }
+
+    let new_dir_name = encode_project_dir(&new_working_dir.to_string_lossy());
+    let new_project_id = root.project_id(&new_dir_name);
+    let target_dir = root.projects_dir.join(&new_dir_name);
+    let old_working_dir = resolve_working_dir(&source_dir);
+
+    let mut blockers = Vec::new();
+    let mut warnings = Vec::new();
+
+    if !new_working_dir.is_absolute() || !new_working_dir.is_dir() {
+        blockers.push(format!(
+            "{} is not an existing absolute directory",
+            new_working_dir.display()
+        ));
+    }
+    if new_project_id == project_id {
+        blockers.push("The project already belongs to that directory".to_string());
+    } else if target_dir.exists() {
+        blockers.push(format!(
+            "Claude Code already has a project for that directory ({})",
+            new_dir_name
+        ));
+    }
+    match &old_working_dir {
+        None => blockers.push("The project's current working directory is unknown".to_string()),
+        Some(old) if old.exists() => warnings.push(format!(
+            "{} still exists; Claude Code will start a new project there",
+            old.display()
+        )),
+        Some(_) => {}
+    }
+
+    let mut files = Vec::new();
+    if let Some(old) = &old_working_dir {
+        let (old, new) = (old.to_string_lossy(), new_working_dir.to_string_lossy());
+        for path in transcripts(&source_dir) {
+            let content =
+                fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+            let (_, rewritten_lines) = rewrite_transcript(&content, &old, &new);
+            files.push(RelocationFile {
+                path: relative(&source_dir, &path),
+                rewritten_lines,
+                total_lines: content
+                    .split(|&b| b == b'\n')
+                    .filter(|l| !l.is_empty())
+                    .count(),
+            });
+        }
+    }
+
+    Ok(RelocationPlan {
+        project_id,
+        new_project_id,
+        rewritten_lines: files.iter().map(|f| f.rewritten_lines).sum(),
+        source_dir,
+        target_dir,
+        old_working_dir,
+        new_working_dir: new_working_dir.to_path_buf(),
+        files,
+        blockers,
+        warnings,
+    })
+}
+
+/// Carries out a plan: backs the project directory up under `backup_root`,
+/// rewrites the transcripts and the session index in place, then moves the
+/// directory. On failure the original files are restored. Returns the backup.
+/// Refuses to start while a transcript is still being written.
+pub fn execute_relocation(plan: &RelocationPlan, backup_root: &Path) -> Result<PathBuf> {
+    if let Some(blocker) = plan.blockers.first() {
+        bail!("Cannot relocate {}: {}", plan.project_id, blocker);
+    }
+    let Some(old_working_dir) = &plan.old_working_dir else {
+        bail!(
+            "Cannot relocate {}: unknown working directory",
+            plan.project_id
+        );
+    };
+    if let Some(path) = recently_written(&plan.source_dir) {
+        bail!(
+            "Cannot relocate {}: {} was modified in the last {} seconds; close its session first",
+            plan.project_id,
+            relative(&plan.source_dir, &path),
+            RECENT_WRITE.as_secs()
+        );
+    }
+
+    let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S");
+    let backup_dir = backup_root.join(format!("{}-{}", stamp, plan.project_id));
+    copy_dir(&plan.source_dir, &backup_dir).context("Failed to back up project")?;
+    fs::write(
+        backup_dir.join("relocation.json"),
+        serde_json::to_string_pretty(plan)?,
+    )?;
+
+    let result = rewrite_project(plan, old_working_dir).and_then(|_| {
+        fs::rename(&plan.source_dir, &plan.target_dir).context("Failed to move project directory")
+    });
+
+    if let Err(e) = result {
+        restore(&backup_dir, &plan.source_dir).with_context(|| {
+            format!(
+                "{:#}; restoring from {} also failed",
+                e,
+                backup_dir.display()
+            )
+        })?;
+        return Err(e);
+    }
+
+    Ok(backup_dir)
+}
+
+/// Puts a project directory back the way `backup_dir` recorded it, after a
+/// later step of a relocation failed. Files the backup does not have, such as
+/// leftover temporary files, are removed.
+pub fn restore(backup_dir: &Path, project_dir: &Path) -> Result<()> {
+    for entry in WalkDir::new(project_dir).into_iter().filter_map(|e| e.ok()) {
+        let relative = entry.path().strip_prefix(project_dir)?;
+        if entry.file_type().is_file() && !backup_dir.join(relative).is_file() {
+            fs::remove_file(entry.path())?;
+        }
+    }
+
+    for entry in WalkDir::new(backup_dir).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+        if !entry.file_type().is_file() || path == backup_dir.join("relocation.json") {
+            continue;
+        }
+        let target = project_dir.join(path.strip_prefix(backup_dir)?);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::copy(path, &target)?;
+    }
+    Ok(())
+}
+
+fn rewrite_project(plan: &RelocationPlan, old_working_dir: &Path) -> Result<()> {
+    let old = old_working_dir.to_string_lossy();
+    let new = plan.new_working_dir.to_string_lossy();
+
+    for path in transcripts(&plan.source_dir) {
+        let content = fs::read(&path)?;
+        let (rewritten, count) = rewrite_transcript(&content, &old, &new);
+        if count > 0 {
+            replace_file(&path, &rewritten)?;
+        }
+    }
+
+    // The index records absolute paths of the project and its transcripts
+    let index = plan.source_dir.join("sessions-index.json");
+    if index.exists() {
+        let content = fs::read_to_string(&index)?;
+        let rewritten = replace_json_path(
+            &replace_json_path(
+                &content,
+                &plan.source_dir.to_string_lossy(),
+                &plan.target_dir.to_string_lossy(),
+            ),
+            &old,
+            &new,
+        );
+        if rewritten != content {
+            replace_file(&index, rewritten.as_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Rewrites the `cwd` of every line inside `old` (the directory or below it)
+/// to the same place under `new`. Other bytes are kept as they are, including
+/// lines that are not valid JSON. Returns the new content and lines changed.
+fn rewrite_transcript(content: &[u8], old: &str, new: &str) -> (Vec<u8>, usize) {
+    let mut out = Vec::with_capacity(content.len());
+    let mut count = 0;
+
+    for (i, line) in content.split(|&b| b == b'\n').enumerate() {
+        if i > 0 {
+            out.push(b'\n');
+        }
+        let rewritten = std::str::from_utf8(line)
+            .ok()
+            .and_then(|line| rewrite_cwd_line(line, old, new));
+        match rewritten {
+            Some(rewritten) => {
+                out.extend_from_slice(rewritten.as_bytes());
+                count += 1;
+            }
+            None => out.extend_from_slice(line),
+        }
+    }
+
+    (out, count)
+}
+
+fn rewrite_cwd_line(line: &str, old: &str, new: &str) -> Option<String> {
+    let mut value: Value = serde_json::from_str(line).ok()?;
+    let cwd = value.get("cwd")?.as_str()?.to_string();
+    let rest = match cwd.strip_prefix(old)? {
+        "" => "",
+        rest if rest.starts_with('/') => rest,
+        _ => return None,
+    };
+    let new_cwd = format!("{}{}", new, rest);
+
+    // Replace the field in place so key order and formatting survive
+    let from = format!("\"cwd\":{}", serde_json::to_string(&cwd).ok()?);
+    if line.contains(&from) {
+        let to = format!("\"cwd\":{}", serde_json::to_string(&new_cwd).ok()?);
+        return Some(line.replacen(&from, &to, 1));
+    }
+    value["cwd"] = new_cwd.into();
+    serde_json::to_string(&value).ok()
+}
+
+/// Replaces JSON string values equal to `old` or starting with `old/`.
+fn replace_json_path(content: &str, old: &str, new: &str) -> String {
+    let escaped = |path: &str| {
+        let quoted = serde_json::to_string(path).unwrap_or_default();
+        quoted[..quoted.len().saturating_sub(1)].to_string()
+    };
+    let (old, new) = (escaped(old), escaped(new));
+
+    content
+        .replace(&format!("{}\"", old), &format!("{}\"", new))
+        .replace(&format!("{}/", old), &format!("{}/", new))
+}
+
+/// Every `.jsonl` file of the project, including subagent transcripts kept
+/// in subdirectories.
+fn transcripts(project_dir: &Path) -> Vec<PathBuf> {
+    let mut paths: Vec<PathBuf> = WalkDir::new(project_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("jsonl"))
+        .collect();
+    paths.sort();
+    paths
+}
+
+/// The first transcript modified within [`RECENT_WRITE`]. An mtime in the
+/// future counts as recent.
+fn recently_written(project_dir: &Path) -> Option<PathBuf> {
+    transcripts(project_dir).into_iter().find(|path| {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|modified| modified.elapsed().map_or(true, |age| age < RECENT_WRITE))
+    })
+}
+
+/// Writes a sibling temporary file and renames it over `path`. The temporary
+/// file is removed again if either step fails.
+fn replace_file(path: &Path, content: &[u8]) -> Result<()> {
+    let tmp = path.with_extension("ctx-tmp");
+    let result = fs::write(&tmp, content).and_then(|_| fs::rename(&tmp, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    Ok(result?)
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    for entry in WalkDir::new(from).into_iter() {
+        let entry = entry?;
+        let target = to.join(entry.path().strip_prefix(from)?);
+        if entry.file_type().is_dir() {
+            fs::create_dir_all(&target)?;
+        } else if entry.file_type().is_file() {
+            fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+fn relative(base: &Path, path: &Path) -> String {
+    path.strip_prefix(base)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roots::DEFAULT_ROOT;
+    use std::time::SystemTime;
+
+    /// Dates a transcript back past [`RECENT_WRITE`].
+    fn settle(path: &Path) {
+        let past = SystemTime::now() - RECENT_WRITE * 6;
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .and_then(|f| f.set_modified(past))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rewrite_transcript() {
+        let content = concat!(
+            r#"{"type":"user","cwd":"/old/repo","sessionId":"s"}"#,
+            "\n",
+            r#"{"type":"user","cwd":"/old/repo/src"}"#,
+            "\n",
+            r#"{"type":"user","cwd":"/old/repository"}"#,
+            "\n",
+            "not json\n",
+        );
+
+        let (rewritten, count) = rewrite_transcript(content.as_bytes(), "/old/repo", "/new/repo");
+
+        assert_eq!(count, 2);
+        assert_eq!(
+            String::from_utf8(rewritten).unwrap(),
+            concat!(
+                r#"{"type":"user","cwd":"/new/repo","sessionId":"s"}"#,
+                "\n",
+                r#"{"type":"user","cwd":"/new/repo/src"}"#,
+                "\n",
+                r#"{"type":"user","cwd":"/old/repository"}"#,
+                "\n",
+                "not json\n",
+            )
+        );
+    }
+
+    #[test]
+    fn test_relocation_round_trip() {
+        let root = std::env::temp_dir().join(format!("ctx-relocate-{}", std::process::id()));
+        let projects_dir = root.join("projects");
+        let new_repo = root.join("new-repo");
+        let old_repo = root.join("old-repo");
+        let old_id = encode_project_dir(&old_repo.to_string_lossy());
+        let source = projects_dir.join(&old_id);
+        fs::create_dir_all(&source).unwrap();
+        fs::create_dir_all(&new_repo).unwrap();
+        let line = format!(
+            "{{\"type\":\"user\",\"cwd\":{}}}\n",
+            serde_json::to_string(&old_repo.to_string_lossy()).unwrap()
+        );
+        fs::write(source.join("s1.jsonl"), &line).unwrap();
+        settle(&source.join("s1.jsonl"));
+
+        let claude_root = ClaudeRoot {
+            name: DEFAULT_ROOT.to_string(),
+            projects_dir: projects_dir.clone(),
+        };
+
+        let plan = plan_relocation(&claude_root, &old_id, &new_repo).unwrap();
+        assert!(plan.blockers.is_empty(), "{:?}", plan.blockers);
+        assert_eq!(plan.rewritten_lines, 1);
+
+        let backup = execute_relocation(&plan, &root.join("backups")).unwrap();
+
+        assert!(!source.exists());
+        let moved = fs::read_to_string(plan.target_dir.join("s1.jsonl")).unwrap();
+        assert!(moved.contains(&*new_repo.to_string_lossy()));
+        assert_eq!(fs::read_to_string(backup.join("s1.jsonl")).unwrap(), line);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+    #[test]
+    fn test_failed_move_restores_transcripts() {
+        let root = std::env::temp_dir().join(format!("ctx-relocate-fail-{}", std::process::id()));
+        let projects_dir = root.join("projects");
+        let new_repo = root.join("new-repo");
+        let old_repo = root.join("old-repo");
+        let old_id = encode_project_dir(&old_repo.to_string_lossy());
+        let source = projects_dir.join(&old_id);
+        fs::create_dir_all(source.join("subagents")).unwrap();
+        fs::create_dir_all(&new_repo).unwrap();
+        let cwd = serde_json::to_string(&old_repo.to_string_lossy()).unwrap();
+        let originals = [
+            (
+                "s1.jsonl",
+                format!("{{\"type\":\"user\",\"cwd\":{}}}\nnot json\n", cwd),
+            ),
+            ("subagents/a.jsonl", format!("{{\"cwd\":{}}}", cwd)),
+            (
+                "sessions-index.json",
+                format!("{{\"projectPath\":{}}}", cwd),
+            ),
+        ];
+        for (name, content) in &originals {
+            fs::write(source.join(name), content).unwrap();
+        }
+
+        let claude_root = ClaudeRoot {
+            name: DEFAULT_ROOT.to_string(),
+            projects_dir,
+        };
+        let plan = plan_relocation(&claude_root, &old_id, &new_repo).unwrap();
+        assert!(plan.blockers.is_empty(), "{:?}", plan.blockers);
+
+        // Freshly written transcripts may still be in use
+        let refused = execute_relocation(&plan, &root.join("backups")).unwrap_err();
+        assert!(refused.to_string().contains("modified in the last"));
+        assert!(!root.join("backups").exists());
+
+        // A directory created after planning makes the final rename fail
+        settle(&source.join("s1.jsonl"));
+        settle(&source.join("subagents/a.jsonl"));
+        fs::create_dir_all(&plan.target_dir).unwrap();
+        fs::write(plan.target_dir.join("other.jsonl"), "{}\n").unwrap();
+
+        let failed = execute_relocation(&plan, &root.join("backups")).unwrap_err();
+
+        assert!(failed
+            .to_string()
+            .contains("Failed to move project directory"));
+        for (name, content) in &originals {
+            assert_eq!(
+                fs::read(source.join(name)).unwrap(),
+                content.as_bytes(),
+                "{}",
+                name
+            );
+        }
+        assert_eq!(fs::read_dir(&plan.target_dir).unwrap().count(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_restore_removes_files_not_in_backup() {
+        let root = std::env::temp_dir().join(format!("ctx-restore-{}", std::process::id()));
+        let (backup, project) = (root.join("backup"), root.join("project"));
+        fs::create_dir_all(&backup).unwrap();
+        fs::create_dir_all(project.join("subagents")).unwrap();
+        fs::write(backup.join("s1.jsonl"), "original\n").unwrap();
+        fs::write(backup.join("relocation.json"), "{}").unwrap();
+        fs::write(project.join("s1.jsonl"), "rewritten\n").unwrap();
+        fs::write(project.join("s1.ctx-tmp"), "partial").unwrap();
+        fs::write(project.join("subagents/new.jsonl"), "{}\n").unwrap();
+
+        restore(&backup, &project).unwrap();
+
+        let mut left: Vec<_> = WalkDir::new(&project)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| relative(&project, e.path()))
+            .collect();
+        left.sort();
+        let content = fs::read_to_string(project.join("s1.jsonl")).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(left, ["s1.jsonl"]);
+        assert_eq!(content, "original\n");
+    }
+}
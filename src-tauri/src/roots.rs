@@ -0,0 +1,183 @@
+//! Where Claude Code keeps its history, and how project ids map onto it.
+//!
+//! The default root is `$CLAUDE_CONFIG_DIR/projects`, or `~/.claude/projects`
+//! when the variable is unset. Further Claude config directories can be
+//! listed under `claude_dirs` in `~/.ctx/config.json`. Projects in the default
+//! root keep their directory name as id; the others are prefixed with their
+//! root's name, e.g. `work~-Users-me-api`, so ids stay unique across roots.
+
+use crate::config::CtxConfig;
+use anyhow::{anyhow, Result};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Name of the root Claude Code itself uses.
+pub const DEFAULT_ROOT: &str = "default";
+/// Joins a root name and a directory name in project ids. Claude Code
+/// encodes every non-alphanumeric character of a path as `-`, so it never
+/// occurs in a directory name.
+const ROOT_SEPARATOR: char = '~';
+
+/// A directory holding Claude Code project directories.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ClaudeRoot {
+    pub name: String,
+    pub projects_dir: PathBuf,
+}
+
+/// A project directory inside one of the roots; it may not exist.
+#[derive(Debug, Clone)]
+pub struct ProjectDir {
+    pub id: String,
+    pub root: ClaudeRoot,
+    pub dir_name: String,
+    pub path: PathBuf,
+}
+
+impl ClaudeRoot {
+    pub fn project_id(&self, dir_name: &str) -> String {
+        if self.name == DEFAULT_ROOT {
+            dir_name.to_string()
+        } else {
+            format!("{}{}{}", self.name, ROOT_SEPARATOR, dir_name)
+        }
+    }
+
+    /// The directory name of `project_id`, if the project is in this root.
+    pub fn dir_name<'a>(&self, project_id: &'a str) -> Option<&'a str> {
+        if self.name == DEFAULT_ROOT {
+            return (!project_id.contains(ROOT_SEPARATOR)).then_some(project_id);
+        }
+        project_id
+            .strip_prefix(self.name.as_str())?
+            .strip_prefix(ROOT_SEPARATOR)
+    }
+
+    pub fn project(&self, dir_name: &str) -> ProjectDir {
+        ProjectDir {
+            id: self.project_id(dir_name),
+            root: self.clone(),
+            dir_name: dir_name.to_string(),
+            path: self.projects_dir.join(dir_name),
+        }
+    }
+
+    /// The project directories currently in this root.
+    pub fn projects(&self) -> Vec<ProjectDir> {
+        let Ok(entries) = fs::read_dir(&self.projects_dir) else {
+            return Vec::new();
+        };
+        entries
+            .filter_map(|e| e.ok())
+            .filter(|e| e.path().is_dir())
+            .filter_map(|e| e.file_name().to_str().map(|name| self.project(name)))
+            .collect()
+    }
+}
+
+/// Claude Code's config directory: `$CLAUDE_CONFIG_DIR`, else `~/.claude`.
+pub fn claude_config_dir() -> Result<PathBuf> {
+    if let Some(dir) = std::env::var_os("CLAUDE_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        return Ok(PathBuf::from(dir));
+    }
+    Ok(dirs::home_dir()
+        .ok_or_else(|| anyhow!("Could not determine home directory"))?
+        .join(".claude"))
+}
+
+/// Every root to read, the default one first.
+pub fn claude_roots() -> Result<Vec<ClaudeRoot>> {
+    Ok(resolve_roots()?.0)
+}
+
+/// Like [`claude_roots`], also returning why the configured roots were left
+/// out. A config that cannot be read must not hide the default root, so it
+/// only costs the extra ones.
+pub fn resolve_roots() -> Result<(Vec<ClaudeRoot>, Option<String>)> {
+    let (extra_dirs, config_error) = match CtxConfig::load() {
+        Ok(config) => (config.claude_dirs, None),
+        Err(e) => (
+            BTreeMap::new(),
+            Some(format!(
+                "Ignoring configured Claude roots: invalid config: {:#}",
+                e
+            )),
+        ),
+    };
+    Ok((roots_from(&claude_config_dir()?, &extra_dirs), config_error))
+}
+
+/// Builds the roots from the default config directory and the extra ones
+/// configured by name. Extra entries named `default`, with names that cannot
+/// be told apart in ids, or repeating a directory are ignored.
+fn roots_from(default_dir: &Path, extra_dirs: &BTreeMap<String, PathBuf>) -> Vec<ClaudeRoot> {
+    let mut roots = vec![ClaudeRoot {
+        name: DEFAULT_ROOT.to_string(),
+        projects_dir: default_dir.join("projects"),
+    }];
+
+    for (name, dir) in extra_dirs {
+        let valid_name = !name.is_empty()
+            && name != DEFAULT_ROOT
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        let projects_dir = dir.join("projects");
+        if valid_name && !roots.iter().any(|r| r.projects_dir == projects_dir) {
+            roots.push(ClaudeRoot {
+                name: name.clone(),
+                projects_dir,
+            });
+        }
+    }
+
+    roots
+}
+
+/// Every project directory across all roots.
+pub fn project_dirs() -> Result<Vec<ProjectDir>> {
+    Ok(claude_roots()?
+        .iter()
+        .flat_map(ClaudeRoot::projects)
+        .collect())
+}
+
+/// Locates the directory of `project_id`, whether or not it exists.
+pub fn find_project(project_id: &str) -> Result<ProjectDir> {
+    claude_roots()?
+        .iter()
+        .find_map(|root| root.dir_name(project_id).map(|dir| root.project(dir)))
+        .ok_or_else(|| anyhow!("Unknown project root: {}", project_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_ids_are_unique_across_roots() {
+        let extra = BTreeMap::from([
+            ("work".to_string(), PathBuf::from("/work/.claude")),
+            ("default".to_string(), PathBuf::from("/other")),
+            ("bad~name".to_string(), PathBuf::from("/bad")),
+            ("same".to_string(), PathBuf::from("/home/me/.claude")),
+        ]);
+
+        let roots = roots_from(Path::new("/home/me/.claude"), &extra);
+
+        let names: Vec<_> = roots.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, ["default", "work"]);
+        let (default, work) = (&roots[0], &roots[1]);
+        assert_eq!(default.project_id("-repo"), "-repo");
+        assert_eq!(work.project_id("-repo"), "work~-repo");
+        assert_eq!(work.dir_name("work~-repo"), Some("-repo"));
+        assert_eq!(work.dir_name("-repo"), None);
+        assert_eq!(default.dir_name("work~-repo"), None);
+        assert_eq!(
+            work.project("-repo").path,
+            PathBuf::from("/work/.claude/projects/-repo")
+        );
+    }
+}
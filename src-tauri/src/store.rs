@@ -0,0 +1,327 @@
+use crate::config::ctx_dir;
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Version written by this build; older files are migrated on load.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// User metadata CTX keeps about Claude projects and sessions, persisted in
+/// `~/.ctx/metadata.json` so that nothing under `~/.claude` is modified.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MetadataStore {
+    pub version: u32,
+    /// Keyed by project id.
+    pub projects: BTreeMap<String, ProjectMeta>,
+    /// Keyed by `<project_id>/<session_id>`.
+    pub sessions: BTreeMap<String, SessionMeta>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectMeta {
+    pub favorite: bool,
+    /// Display name shown instead of the directory name.
+    pub alias: Option<String>,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SessionMeta {
+    /// User-assigned title, overriding the derived one.
+    pub title: Option<String>,
+    pub pinned: bool,
+    pub tags: Vec<String>,
+    pub notes: Option<String>,
+}
+
+/// Partial update of a project's metadata; absent fields are left alone and
+/// a blank alias or note clears it.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ProjectMetaUpdate {
+    pub favorite: Option<bool>,
+    pub alias: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub notes: Option<String>,
+}
+
+/// Partial update of a session's metadata, with the same rules as
+/// [`ProjectMetaUpdate`].
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SessionMetaUpdate {
+    pub title: Option<String>,
+    pub pinned: Option<bool>,
+    pub tags: Option<Vec<String>>,
+    pub notes: Option<String>,
+}
+
+impl Default for MetadataStore {
+    fn default() -> Self {
+        Self {
+            version: SCHEMA_VERSION,
+            projects: BTreeMap::new(),
+            sessions: BTreeMap::new(),
+        }
+    }
+}
+
+impl MetadataStore {
+    pub fn path() -> Result<PathBuf> {
+        Ok(ctx_dir()?.join("metadata.json"))
+    }
+
+    /// Loads the store, starting empty when the file does not exist and
+    /// migrating files written by older versions.
+    pub fn load() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)?;
+        Self::from_value(serde_json::from_str(&content)?)
+    }
+
+    /// Writes through a temporary file so a crash never leaves a torn store.
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let tmp = path.with_extension("json.tmp");
+        fs::write(&tmp, serde_json::to_string_pretty(self)?)?;
+        fs::rename(&tmp, &path)?;
+        Ok(())
+    }
+
+    fn from_value(mut value: Value) -> Result<Self> {
+        // Version 1 predates the field
+        let mut version = value.get("version").and_then(|v| v.as_u64()).unwrap_or(1) as u32;
+        if version > SCHEMA_VERSION {
+            bail!(
+                "metadata schema version {} is newer than supported ({})",
+                version,
+                SCHEMA_VERSION
+            );
+        }
+
+        while version < SCHEMA_VERSION {
+            migrate(&mut value, version)?;
+            version += 1;
+        }
+        value["version"] = SCHEMA_VERSION.into();
+
+        Ok(serde_json::from_value(value)?)
+    }
+
+    pub fn project(&self, project_id: &str) -> ProjectMeta {
+        self.projects.get(project_id).cloned().unwrap_or_default()
+    }
+
+    pub fn session(&self, project_id: &str, session_id: &str) -> SessionMeta {
+        self.sessions
+            .get(&session_key(project_id, session_id))
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// Metadata of every session of a project that has any, by session id.
+    pub fn project_sessions(&self, project_id: &str) -> BTreeMap<String, SessionMeta> {
+        let prefix = format!("{}/", project_id);
+        self.sessions
+            .iter()
+            .filter_map(|(key, meta)| Some((key.strip_prefix(&prefix)?.to_string(), meta.clone())))
+            .collect()
+    }
+
+    pub fn session_title(&self, project_id: &str, session_id: &str) -> Option<&str> {
+        self.sessions
+            .get(&session_key(project_id, session_id))
+            .and_then(|meta| meta.title.as_deref())
+    }
+
+    /// Sets or, with `None` or a blank title, clears a session's title.
+    pub fn set_session_title(&mut self, project_id: &str, session_id: &str, title: Option<String>) {
+        self.update_session(
+            project_id,
+            session_id,
+            SessionMetaUpdate {
+                title: Some(title.unwrap_or_default()),
+                ..Default::default()
+            },
+        );
+    }
+
+    pub fn update_project(&mut self, project_id: &str, update: ProjectMetaUpdate) -> ProjectMeta {
+        let meta = self.projects.entry(project_id.to_string()).or_default();
+        if let Some(favorite) = update.favorite {
+            meta.favorite = favorite;
+        }
+        if let Some(alias) = update.alias {
+            meta.alias = non_blank(alias);
+        }
+        if let Some(tags) = update.tags {
+            meta.tags = normalize_tags(tags);
+        }
+        if let Some(notes) = update.notes {
+            meta.notes = non_blank(notes);
+        }
+
+        let meta = meta.clone();
+        if meta == ProjectMeta::default() {
+            self.projects.remove(project_id);
+        }
+        meta
+    }
+
+    pub fn update_session(
+        &mut self,
+        project_id: &str,
+        session_id: &str,
+        update: SessionMetaUpdate,
+    ) -> SessionMeta {
+        let key = session_key(project_id, session_id);
+        let meta = self.sessions.entry(key.clone()).or_default();
+        if let Some(title) = update.title {
+            meta.title = non_blank(title);
+        }
+        if let Some(pinned) = update.pinned {
+            meta.pinned = pinned;
+        }
+        if let Some(tags) = update.tags {
+            meta.tags = normalize_tags(tags);
+        }
+        if let Some(notes) = update.notes {
+            meta.notes = non_blank(notes);
+        }
+
+        let meta = meta.clone();
+        if meta == SessionMeta::default() {
+            self.sessions.remove(&key);
+        }
+        meta
+    }
+
+    /// Moves all metadata of a project and its sessions to a new project id,
+    /// after its directory under `~/.claude/projects` was relocated.
+    pub fn move_project(&mut self, old_id: &str, new_id: &str) {
+        if let Some(meta) = self.projects.remove(old_id) {
+            self.projects.insert(new_id.to_string(), meta);
+        }
+        for (session_id, meta) in self.project_sessions(old_id) {
+            self.sessions.remove(&session_key(old_id, &session_id));
+            self.sessions.insert(session_key(new_id, &session_id), meta);
+        }
+    }
+}
+
+/// Upgrades `value` from schema `from` to `from + 1`. The file is user
+/// editable, so an unknown version is an error rather than a bug.
+fn migrate(value: &mut Value, from: u32) -> Result<()> {
+    match from {
+        // v1 only had `session_titles: { "<project>/<session>": title }`
+        1 => {
+            let titles = value
+                .get_mut("session_titles")
+                .map(Value::take)
+                .unwrap_or_default();
+            let mut sessions = serde_json::Map::new();
+            if let Value::Object(titles) = titles {
+                for (key, title) in titles {
+                    sessions.insert(key, serde_json::json!({ "title": title }));
+                }
+            }
+            *value = serde_json::json!({ "sessions": sessions });
+        }
+        _ => bail!("unknown metadata schema version {}", from),
+    }
+    Ok(())
+}
+
+fn session_key(project_id: &str, session_id: &str) -> String {
+    format!("{}/{}", project_id, session_id)
+}
+
+fn non_blank(text: String) -> Option<String> {
+    let text = text.trim();
+    (!text.is_empty()).then(|| text.to_string())
+}
+
+/// Trims, drops empty and duplicate tags, keeping the given order.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim();
+        if !tag.is_empty() && !normalized.iter().any(|t| t == tag) {
+            normalized.push(tag.to_string());
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrates_v1_session_titles() {
+        let v1 = serde_json::json!({ "session_titles": { "proj/s1": "Fix login" } });
+
+        let store = MetadataStore::from_value(v1).unwrap();
+
+        assert_eq!(store.version, SCHEMA_VERSION);
+        assert_eq!(store.session_title("proj", "s1"), Some("Fix login"));
+        assert!(MetadataStore::from_value(serde_json::json!({ "version": 99 })).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_schema_version() {
+        let err = MetadataStore::from_value(serde_json::json!({ "version": 0 })).unwrap_err();
+
+        assert!(err.to_string().contains("version 0"), "{}", err);
+    }
+
+    #[test]
+    fn test_updates_drop_empty_entries() {
+        let mut store = MetadataStore::default();
+
+        let meta = store.update_project(
+            "proj",
+            ProjectMetaUpdate {
+                favorite: Some(true),
+                tags: Some(vec![" api ".into(), "api".into(), "".into()]),
+                ..Default::default()
+            },
+        );
+        assert_eq!(meta.tags, ["api"]);
+
+        store.update_project(
+            "proj",
+            ProjectMetaUpdate {
+                favorite: Some(false),
+                tags: Some(Vec::new()),
+                ..Default::default()
+            },
+        );
+        assert!(store.projects.is_empty());
+
+        store.update_session(
+            "proj",
+            "s1",
+            SessionMetaUpdate {
+                pinned: Some(true),
+                ..Default::default()
+            },
+        );
+        assert!(store.project_sessions("proj")["s1"].pinned);
+        assert!(store.project_sessions("pro").is_empty());
+    }
+}
@@ -0,0 +1,61 @@
+use crate::models::Message;
+use serde::{Deserialize, Serialize};
+
+/// Payload of `watcher:project-added` and `watcher:project-removed`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProjectChanged {
+    pub project_id: String,
+}
+
+/// Payload of the `watcher:session-*` events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionChanged {
+    pub project_id: String,
+    pub session_id: String,
+    /// Transcript size after the change; 0 once removed.
+    pub size: u64,
+}
+
+/// Payload of `watcher:session-messages`: entries appended to a transcript
+/// since the watcher last read it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionMessages {
+    pub project_id: String,
+    pub session_id: String,
+    pub messages: Vec<Message>,
+    pub is_waiting: bool,
+    /// Set when the transcript was rewritten and `messages` holds all of it.
+    pub reset: bool,
+    /// Why the transcript could not be read; `messages` is then empty and the
+    /// next change to the file is read from the same place.
+    pub error: Option<String>,
+}
+
+impl SessionMessages {
+    pub const EVENT: &'static str = "watcher:session-messages";
+}
+
+/// A change to Claude Code's history on disk, as reported to the frontend.
+///
+/// Event names are prefixed with `watcher:` so they do not collide with the
+/// live-session events emitted by the process manager.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchEvent {
+    ProjectAdded(ProjectChanged),
+    ProjectRemoved(ProjectChanged),
+    SessionCreated(SessionChanged),
+    SessionAppended(SessionChanged),
+    SessionRemoved(SessionChanged),
+}
+
+impl WatchEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            WatchEvent::ProjectAdded(_) => "watcher:project-added",
+            WatchEvent::ProjectRemoved(_) => "watcher:project-removed",
+            WatchEvent::SessionCreated(_) => "watcher:session-created",
+            WatchEvent::SessionAppended(_) => "watcher:session-appended",
+            WatchEvent::SessionRemoved(_) => "watcher:session-removed",
+        }
+    }
+}
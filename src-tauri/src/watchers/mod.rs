@@ -1 +1,3 @@
+pub mod events;
+pub mod service;
 pub mod session_watcher;
@@ -0,0 +1,175 @@
+use super::session_watcher::{watch, WatchState};
+use crate::roots::ClaudeRoot;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// How often an idle supervisor checks for the root or a resume.
+const IDLE_POLL: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatcherStatus {
+    Starting,
+    /// The root does not exist yet, e.g. Claude Code was never run.
+    WaitingForRoot,
+    Running,
+    Paused,
+    /// The watcher failed and is about to be started again.
+    Restarting,
+}
+
+/// Payload of `watcher:health`, emitted whenever the status changes.
+#[derive(Debug, Clone, Serialize)]
+pub struct WatcherHealth {
+    pub status: WatcherStatus,
+    pub root: ClaudeRoot,
+    /// When the current status was entered.
+    pub since: DateTime<Utc>,
+    pub restarts: u32,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+    /// Why configured roots were left out, reported on the default root.
+    pub config_error: Option<String>,
+}
+
+impl WatcherHealth {
+    pub const EVENT: &'static str = "watcher:health";
+}
+
+/// Keeps a history watcher running on every Claude root for the lifetime of
+/// the app: waits for a root to appear, restarts its watcher after errors and
+/// lets the frontend pause them all.
+pub struct WatcherService {
+    health: Vec<Arc<Mutex<WatcherHealth>>>,
+    paused: Arc<AtomicBool>,
+}
+
+impl WatcherService {
+    /// Starts supervising a watcher per root in background threads.
+    /// `config_error` is shown in the health of the first (default) root.
+    pub fn start(
+        app_handle: AppHandle,
+        roots: Vec<ClaudeRoot>,
+        mut config_error: Option<String>,
+    ) -> Self {
+        let paused = Arc::new(AtomicBool::new(false));
+        let mut health = Vec::new();
+
+        for root in roots {
+            let root_health = Arc::new(Mutex::new(WatcherHealth {
+                status: WatcherStatus::Starting,
+                root: root.clone(),
+                since: Utc::now(),
+                restarts: 0,
+                last_error: None,
+                last_error_at: None,
+                config_error: config_error.take(),
+            }));
+            let supervisor = Supervisor {
+                app_handle: app_handle.clone(),
+                root,
+                health: root_health.clone(),
+                paused: paused.clone(),
+            };
+            std::thread::spawn(move || supervisor.run());
+            health.push(root_health);
+        }
+
+        WatcherService { health, paused }
+    }
+
+    /// The health of each root's watcher, the default root first.
+    pub fn health(&self) -> Result<Vec<WatcherHealth>, String> {
+        self.health
+            .iter()
+            .map(|health| {
+                health
+                    .lock()
+                    .map(|health| health.clone())
+                    .map_err(|e| e.to_string())
+            })
+            .collect()
+    }
+
+    /// Stops watching until [`resume`](Self::resume); changes made meanwhile
+    /// are reported on resume.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+}
+
+struct Supervisor {
+    app_handle: AppHandle,
+    root: ClaudeRoot,
+    health: Arc<Mutex<WatcherHealth>>,
+    paused: Arc<AtomicBool>,
+}
+
+impl Supervisor {
+    fn run(self) {
+        // A root that appears later is new in its entirety
+        let mut state = if self.root.projects_dir.is_dir() {
+            WatchState::scan(&self.root)
+        } else {
+            WatchState::empty(&self.root)
+        };
+        let mut failures = 0;
+
+        loop {
+            if self.paused.load(Ordering::Relaxed) {
+                self.set_status(WatcherStatus::Paused, None);
+                std::thread::sleep(IDLE_POLL);
+                continue;
+            }
+            if !self.root.projects_dir.is_dir() {
+                self.set_status(WatcherStatus::WaitingForRoot, None);
+                std::thread::sleep(IDLE_POLL);
+                continue;
+            }
+
+            self.set_status(WatcherStatus::Running, None);
+            match watch(&self.app_handle, &mut state, &self.paused) {
+                Ok(()) => failures = 0,
+                Err(e) => {
+                    eprintln!("Watch error: {:?}", e);
+                    failures += 1;
+                    self.set_status(WatcherStatus::Restarting, Some(format!("{:#}", e)));
+                    std::thread::sleep(backoff(failures));
+                }
+            }
+        }
+    }
+
+    /// Records a status change and reports it to the frontend.
+    fn set_status(&self, status: WatcherStatus, error: Option<String>) {
+        let Ok(mut health) = self.health.lock() else {
+            return;
+        };
+        if health.status == status && error.is_none() {
+            return;
+        }
+
+        let now = Utc::now();
+        health.status = status;
+        health.since = now;
+        if let Some(error) = error {
+            health.restarts += 1;
+            health.last_error = Some(error);
+            health.last_error_at = Some(now);
+        }
+        let _ = self.app_handle.emit(WatcherHealth::EVENT, health.clone());
+    }
+}
+
+/// Delay before the `failures`th consecutive restart: 1s, doubling up to 32s.
+fn backoff(failures: u32) -> Duration {
+    Duration::from_secs(1 << (failures.clamp(1, 6) - 1))
+}
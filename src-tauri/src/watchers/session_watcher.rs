@@ -1,45 +1,528 @@
-use anyhow::Result;
-use notify::{Watcher, RecursiveMode, RecommendedWatcher};
-use std::sync::mpsc;
-use std::time::Duration;
-use tauri::AppHandle;
+use super::events::{ProjectChanged, SessionChanged, SessionMessages, WatchEvent};
+use crate::models::TailCursor;
+use crate::parsers::{parse_session_tail, tail_cursor_at};
+use crate::roots::ClaudeRoot;
+use anyhow::{bail, Result};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
 
-pub fn start_watching(_app_handle: AppHandle) -> Result<()> {
-    let projects_dir = dirs::home_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine home directory"))?
-        .join(".claude/projects");
-
-    if !projects_dir.exists() {
-        return Ok(());
-    }
+/// Quiet period after the last change before a path is reported.
+const DEBOUNCE: Duration = Duration::from_millis(250);
+/// Longest a continuously written file waits before being reported anyway.
+const MAX_DELAY: Duration = Duration::from_secs(1);
 
+/// Watches the root of `state` until `paused` is set or the root disappears,
+/// emitting a
+/// [`WatchEvent`] for every project or transcript that appears, grows or
+/// disappears, followed by the [`SessionMessages`] written to a transcript
+/// since it was last read. Anything that changed since `state` was last
+/// synced is reported first. Blocks while watching; an error means the
+/// underlying watcher failed and should be restarted.
+pub fn watch(app_handle: &AppHandle, state: &mut WatchState, paused: &AtomicBool) -> Result<()> {
+    let root = state.root.projects_dir.clone();
     let (tx, rx) = mpsc::channel();
 
     let mut watcher = RecommendedWatcher::new(
         move |res| {
             let _ = tx.send(res);
         },
-        notify::Config::default()
-            .with_poll_interval(Duration::from_millis(100)),
+        notify::Config::default().with_poll_interval(Duration::from_millis(100)),
     )?;
 
-    watcher.watch(&projects_dir, RecursiveMode::Recursive)?;
+    watcher.watch(&root, RecursiveMode::Recursive)?;
 
-    // Process events in background thread
-    std::thread::spawn(move || {
-        for res in rx {
-            match res {
-                Ok(event) => {
-                    // For now, just log events
-                    // In the future, emit to frontend via app_handle.emit_all()
-                    println!("File event: {:?}", event);
-                }
-                Err(e) => {
-                    eprintln!("Watch error: {:?}", e);
+    // Catch up on whatever changed while nothing was watching
+    let events = state.rescan();
+    publish(app_handle, state, events);
+    let mut debouncer = Debouncer::default();
+
+    while !paused.load(Ordering::Relaxed) {
+        match rx.recv_timeout(DEBOUNCE) {
+            // The event queue overflowed, so individual changes were lost
+            Ok(Ok(event)) if event.need_rescan() => {
+                debouncer = Debouncer::default();
+                let events = state.rescan();
+                publish(app_handle, state, events);
+            }
+            Ok(Ok(event)) => {
+                let now = Instant::now();
+                for path in event.paths {
+                    if let Some(path) = state.watched_path(&path) {
+                        debouncer.touch(path, now);
+                    }
                 }
             }
+            Ok(Err(e)) => return Err(e.into()),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => bail!("File watcher stopped unexpectedly"),
+        }
+
+        if !root.is_dir() {
+            let events = state.rescan();
+            publish(app_handle, state, events);
+            return Ok(());
         }
-    });
+
+        for path in debouncer.ready(Instant::now()) {
+            let events = state.update(&path);
+            publish(app_handle, state, events);
+        }
+    }
 
     Ok(())
 }
+
+/// Emits `events`, each session change followed by its new messages.
+fn publish(app_handle: &AppHandle, state: &mut WatchState, events: Vec<WatchEvent>) {
+    for event in events {
+        emit(app_handle, &event);
+        if let WatchEvent::SessionCreated(changed) | WatchEvent::SessionAppended(changed) = &event {
+            if let Some(tail) = state.tail(changed) {
+                let _ = app_handle.emit(SessionMessages::EVENT, tail);
+            }
+        }
+    }
+}
+
+fn emit(app_handle: &AppHandle, event: &WatchEvent) {
+    let _ = match event {
+        WatchEvent::ProjectAdded(payload) | WatchEvent::ProjectRemoved(payload) => {
+            app_handle.emit(event.name(), payload)
+        }
+        WatchEvent::SessionCreated(payload)
+        | WatchEvent::SessionAppended(payload)
+        | WatchEvent::SessionRemoved(payload) => app_handle.emit(event.name(), payload),
+    };
+}
+
+/// The projects and transcript sizes last seen on disk. Events come from
+/// comparing a path's current state with this snapshot rather than from the
+/// notify event kinds, which differ between platforms.
+pub struct WatchState {
+    root: ClaudeRoot,
+    /// Project directory names.
+    projects: BTreeSet<String>,
+    /// Transcript sizes keyed by `(dir_name, session_id)`.
+    sessions: BTreeMap<(String, String), u64>,
+    /// How far each transcript has been tailed. Transcripts present at
+    /// startup get theirs on their first change, so history is not replayed.
+    cursors: HashMap<(String, String), TailCursor>,
+}
+
+impl WatchState {
+    /// Starts from what is on disk now, so none of it is reported.
+    pub fn scan(root: &ClaudeRoot) -> Self {
+        let mut state = Self::empty(root);
+        let _ = state.rescan();
+        state
+    }
+
+    /// Starts from nothing, so everything under `root` is reported as new
+    /// once it appears.
+    pub fn empty(root: &ClaudeRoot) -> Self {
+        WatchState {
+            root: root.clone(),
+            projects: BTreeSet::new(),
+            sessions: BTreeMap::new(),
+            cursors: HashMap::new(),
+        }
+    }
+
+    /// Brings every project on disk or previously known in line, for when
+    /// individual changes may have been missed.
+    fn rescan(&mut self) -> Vec<WatchEvent> {
+        let dir_names: BTreeSet<String> = list_dir(&self.root.projects_dir, |path| path.is_dir())
+            .into_iter()
+            .chain(self.projects.iter().cloned())
+            .collect();
+        dir_names
+            .iter()
+            .flat_map(|dir_name| self.sync_project(dir_name))
+            .collect()
+    }
+
+    /// Reduces an event path to the project directory or transcript it
+    /// concerns; anything deeper (subagent folders, temp files) is ignored.
+    fn watched_path(&self, path: &Path) -> Option<PathBuf> {
+        let parts = relative_parts(&self.root.projects_dir, path)?;
+        match parts.as_slice() {
+            [_] => Some(path.to_path_buf()),
+            [_, file] if file.ends_with(".jsonl") => Some(path.to_path_buf()),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, path: &Path) -> Vec<WatchEvent> {
+        let Some(parts) = relative_parts(&self.root.projects_dir, path) else {
+            return Vec::new();
+        };
+        match parts.as_slice() {
+            [dir_name] => self.sync_project(dir_name),
+            [dir_name, file] => {
+                let Some(session_id) = file.strip_suffix(".jsonl") else {
+                    return Vec::new();
+                };
+                let mut events = Vec::new();
+                if !self.projects.contains(dir_name.as_str()) {
+                    events.extend(self.sync_project(dir_name));
+                } else {
+                    events.extend(self.sync_session(dir_name, session_id));
+                }
+                events
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Brings a whole project in line with the disk, including its sessions.
+    fn sync_project(&mut self, dir_name: &str) -> Vec<WatchEvent> {
+        let dir = self.root.projects_dir.join(dir_name);
+        let payload = ProjectChanged {
+            project_id: self.root.project_id(dir_name),
+        };
+        let mut events = Vec::new();
+
+        if !dir.is_dir() {
+            if self.projects.remove(dir_name) {
+                for session_id in self.known_sessions(dir_name) {
+                    events.extend(self.sync_session(dir_name, &session_id));
+                }
+                events.push(WatchEvent::ProjectRemoved(payload));
+            }
+            return events;
+        }
+
+        if self.projects.insert(dir_name.to_string()) {
+            events.push(WatchEvent::ProjectAdded(payload));
+        }
+        let on_disk = list_dir(&dir, |path| {
+            path.extension().and_then(|e| e.to_str()) == Some("jsonl")
+        });
+        let session_ids: BTreeSet<String> = on_disk
+            .iter()
+            .filter_map(|file| file.strip_suffix(".jsonl").map(String::from))
+            .chain(self.known_sessions(dir_name))
+            .collect();
+        for session_id in session_ids {
+            events.extend(self.sync_session(dir_name, &session_id));
+        }
+        events
+    }
+
+    fn sync_session(&mut self, dir_name: &str, session_id: &str) -> Option<WatchEvent> {
+        let key = (dir_name.to_string(), session_id.to_string());
+        let path = self
+            .root
+            .projects_dir
+            .join(dir_name)
+            .join(format!("{}.jsonl", session_id));
+        let size = fs::metadata(&path)
+            .ok()
+            .filter(|m| m.is_file())
+            .map(|m| m.len());
+        let project_id = self.root.project_id(dir_name);
+        let payload = |size| SessionChanged {
+            project_id: project_id.clone(),
+            session_id: session_id.to_string(),
+            size,
+        };
+
+        match (self.sessions.get(&key).copied(), size) {
+            (None, Some(size)) => {
+                self.sessions.insert(key, size);
+                Some(WatchEvent::SessionCreated(payload(size)))
+            }
+            (Some(known), Some(size)) if known != size => {
+                if !self.cursors.contains_key(&key) {
+                    // A shrunk file is read again from the start by the tail
+                    let cursor = (size > known)
+                        .then(|| tail_cursor_at(&path, known).ok())
+                        .flatten()
+                        .unwrap_or(TailCursor {
+                            offset: known,
+                            ..Default::default()
+                        });
+                    self.cursors.insert(key.clone(), cursor);
+                }
+                self.sessions.insert(key, size);
+                Some(WatchEvent::SessionAppended(payload(size)))
+            }
+            (Some(_), None) => {
+                self.sessions.remove(&key);
+                self.cursors.remove(&key);
+                Some(WatchEvent::SessionRemoved(payload(0)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Reads what was appended to a transcript since the last call, or
+    /// `None` when nothing complete was added. A failed read is reported in
+    /// the returned payload.
+    fn tail(&mut self, changed: &SessionChanged) -> Option<SessionMessages> {
+        let dir_name = self.root.dir_name(&changed.project_id)?;
+        let key = (dir_name.to_string(), changed.session_id.clone());
+        let path = self
+            .root
+            .projects_dir
+            .join(dir_name)
+            .join(format!("{}.jsonl", changed.session_id));
+        let cursor = self.cursors.get(&key).cloned().unwrap_or_default();
+
+        let delta = match parse_session_tail(&path, &cursor) {
+            Ok(delta) => delta,
+            Err(e) => {
+                return Some(SessionMessages {
+                    project_id: changed.project_id.clone(),
+                    session_id: changed.session_id.clone(),
+                    messages: Vec::new(),
+                    is_waiting: false,
+                    reset: false,
+                    error: Some(format!("Failed to tail {}: {:#}", path.display(), e)),
+                });
+            }
+        };
+        self.cursors.insert(key, delta.cursor);
+
+        (!delta.messages.is_empty() || delta.reset).then(|| SessionMessages {
+            project_id: changed.project_id.clone(),
+            session_id: changed.session_id.clone(),
+            messages: delta.messages,
+            is_waiting: delta.is_waiting,
+            reset: delta.reset,
+            error: None,
+        })
+    }
+
+    fn known_sessions(&self, dir_name: &str) -> Vec<String> {
+        self.sessions
+            .keys()
+            .filter(|(project, _)| project == dir_name)
+            .map(|(_, session)| session.clone())
+            .collect()
+    }
+}
+
+/// Collects paths touched by a burst of writes and releases each one once it
+/// has been quiet for [`DEBOUNCE`], or after [`MAX_DELAY`] at the latest.
+#[derive(Default)]
+struct Debouncer {
+    /// First and latest change per path.
+    pending: HashMap<PathBuf, (Instant, Instant)>,
+}
+
+impl Debouncer {
+    fn touch(&mut self, path: PathBuf, now: Instant) {
+        self.pending
+            .entry(path)
+            .and_modify(|(_, last)| *last = now)
+            .or_insert((now, now));
+    }
+
+    fn ready(&mut self, now: Instant) -> Vec<PathBuf> {
+        let mut ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, (first, last))| {
+                now.duration_since(*last) >= DEBOUNCE || now.duration_since(*first) >= MAX_DELAY
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        ready.sort();
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready
+    }
+}
+
+fn relative_parts(root: &Path, path: &Path) -> Option<Vec<String>> {
+    let parts: Vec<String> = path
+        .strip_prefix(root)
+        .ok()?
+        .components()
+        .map(|c| match c {
+            Component::Normal(name) => name.to_str().map(String::from),
+            _ => None,
+        })
+        .collect::<Option<_>>()?;
+    (!parts.is_empty()).then_some(parts)
+}
+
+fn list_dir(dir: &Path, keep: impl Fn(&Path) -> bool) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| keep(path))
+        .filter_map(|path| path.file_name()?.to_str().map(String::from))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::roots::DEFAULT_ROOT;
+
+    fn claude_root(name: &str, projects_dir: &Path) -> ClaudeRoot {
+        ClaudeRoot {
+            name: name.to_string(),
+            projects_dir: projects_dir.to_path_buf(),
+        }
+    }
+
+    fn session(project: &str, session: &str, size: u64) -> SessionChanged {
+        SessionChanged {
+            project_id: project.to_string(),
+            session_id: session.to_string(),
+            size,
+        }
+    }
+
+    #[test]
+    fn test_watch_state_classifies_changes() {
+        let root = std::env::temp_dir().join(format!("ctx-watch-{}", std::process::id()));
+        let transcript = root.join("-repo").join("s1.jsonl");
+        fs::create_dir_all(transcript.parent().unwrap()).unwrap();
+        let mut state = WatchState::scan(&claude_root(DEFAULT_ROOT, &root));
+
+        fs::write(&transcript, "{}\n").unwrap();
+        assert_eq!(
+            state.update(&transcript),
+            [WatchEvent::SessionCreated(session("-repo", "s1", 3))]
+        );
+        assert!(state.update(&transcript).is_empty());
+
+        fs::write(&transcript, "{}\n{}\n").unwrap();
+        assert_eq!(
+            state.update(&transcript),
+            [WatchEvent::SessionAppended(session("-repo", "s1", 6))]
+        );
+
+        let other = root.join("-other").join("s2.jsonl");
+        fs::create_dir_all(other.parent().unwrap()).unwrap();
+        fs::write(&other, "").unwrap();
+        let added = ProjectChanged {
+            project_id: "-other".to_string(),
+        };
+        assert_eq!(
+            state.update(&other),
+            [
+                WatchEvent::ProjectAdded(added.clone()),
+                WatchEvent::SessionCreated(session("-other", "s2", 0)),
+            ]
+        );
+
+        fs::remove_dir_all(root.join("-other")).unwrap();
+        assert_eq!(
+            state.update(&root.join("-other")),
+            [
+                WatchEvent::SessionRemoved(session("-other", "s2", 0)),
+                WatchEvent::ProjectRemoved(added),
+            ]
+        );
+
+        assert_eq!(
+            state.watched_path(&root.join("-repo/s1/subagents/a.jsonl")),
+            None
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_watch_state_tails_new_lines() {
+        let root = std::env::temp_dir().join(format!("ctx-tail-{}", std::process::id()));
+        let transcript = root.join("-repo").join("s1.jsonl");
+        fs::create_dir_all(transcript.parent().unwrap()).unwrap();
+        let line = |uuid: &str| {
+            format!(
+                "{{\"type\":\"user\",\"uuid\":\"{}\",\"message\":{{\"role\":\"user\",\"content\":\"hi\"}}}}\n",
+                uuid
+            )
+        };
+        fs::write(&transcript, line("old")).unwrap();
+        let mut state = WatchState::scan(&claude_root("work", &root));
+
+        // History from before startup is not replayed
+        fs::write(&transcript, line("old") + &line("new")).unwrap();
+        let [WatchEvent::SessionAppended(changed)] = &state.update(&transcript)[..] else {
+            panic!("expected an append");
+        };
+        let changed = changed.clone();
+        let tail = state.tail(&changed).unwrap();
+        let ids: Vec<_> = tail.messages.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["new"]);
+        assert_eq!(
+            (tail.project_id.as_str(), tail.reset),
+            ("work~-repo", false)
+        );
+        assert!(state.tail(&changed).is_none());
+
+        fs::remove_file(&transcript).unwrap();
+        let failed = state.tail(&changed).unwrap();
+        assert!(failed.messages.is_empty());
+        assert!(failed.error.unwrap().starts_with("Failed to tail"));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_rescan_reports_a_root_created_later() {
+        let root = std::env::temp_dir().join(format!("ctx-rescan-{}", std::process::id()));
+        let mut state = WatchState::empty(&claude_root(DEFAULT_ROOT, &root));
+        assert!(state.rescan().is_empty());
+
+        fs::create_dir_all(root.join("-repo")).unwrap();
+        fs::write(root.join("-repo").join("s1.jsonl"), "").unwrap();
+        let added = ProjectChanged {
+            project_id: "-repo".to_string(),
+        };
+        assert_eq!(
+            state.rescan(),
+            [
+                WatchEvent::ProjectAdded(added.clone()),
+                WatchEvent::SessionCreated(session("-repo", "s1", 0)),
+            ]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(
+            state.rescan(),
+            [
+                WatchEvent::SessionRemoved(session("-repo", "s1", 0)),
+                WatchEvent::ProjectRemoved(added),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_debouncer() {
+        let start = Instant::now();
+        let path = PathBuf::from("/p/s.jsonl");
+        let mut debouncer = Debouncer::default();
+
+        debouncer.touch(path.clone(), start);
+        debouncer.touch(path.clone(), start + Duration::from_millis(200));
+        assert!(debouncer
+            .ready(start + Duration::from_millis(300))
+            .is_empty());
+        assert_eq!(
+            debouncer.ready(start + Duration::from_millis(450)),
+            std::slice::from_ref(&path)
+        );
+
+        // A file written continuously is still reported after MAX_DELAY
+        for ms in (0..=1000).step_by(100) {
+            debouncer.touch(path.clone(), start + Duration::from_millis(ms));
+        }
+        assert_eq!(debouncer.ready(start + Duration::from_millis(1000)), [path]);
+    }
+}
@@ -1,4 +1,5 @@
 import { useEffect, useState, useRef } from 'react'
+import { listen } from '@tauri-apps/api/event'
 import { LeftSidebar } from './LeftSidebar'
 import { RightSidebar } from './RightSidebar'
 import { NeuralCanvas } from '../visualization/NeuralCanvas'
@@ -7,7 +8,7 @@ import { SettingsPanel } from '../settings/SettingsPanel'
 import { SessionPanel } from '../sessions/SessionPanel'
 import { useProjectStore } from '../../stores/useProjectStore'
 import { useNotificationStore } from '../../stores/useNotificationStore'
-import { tauriService } from '../../services/tauriService'
+import { tauriService, WATCHER_EVENTS } from '../../services/tauriService'
 
 const DEFAULT_LEFT_WIDTH = 256
 const DEFAULT_RIGHT_WIDTH = 320
@@ -33,12 +34,13 @@ export function MainLayout() {
   const startWidthRef = useRef(0)
 
   useEffect(() => {
-    const loadProjects = async () => {
+    const loadProjects = async (notify: boolean) => {
       setIsLoading(true)
       try {
         const projectList = await tauriService.scanProjects()
         setProjects(projectList)
 
+        if (!notify) return
         if (projectList.length > 0) {
           addNotification({
             type: 'success',
@@ -67,12 +69,19 @@ export function MainLayout() {
       }
     }
 
-    loadProjects()
+    loadProjects(true)
 
-    // Refresh projects every 5 seconds
-    const interval = setInterval(loadProjects, 5000)
+    // Refresh when the watcher reports changes instead of polling
+    const unlisteners = [
+      WATCHER_EVENTS.projectAdded,
+      WATCHER_EVENTS.projectRemoved,
+      WATCHER_EVENTS.sessionCreated,
+      WATCHER_EVENTS.sessionRemoved,
+    ].map((event) => listen(event, () => loadProjects(false)))
 
-    return () => clearInterval(interval)
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()))
+    }
   }, [setProjects, addNotification])
 
   // Sidebar resizing
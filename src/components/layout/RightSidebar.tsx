@@ -1,8 +1,9 @@
 import { useState, useEffect } from 'react'
+import { listen } from '@tauri-apps/api/event'
 import { useProjectStore } from '../../stores/useProjectStore'
 import { useThreadStore } from '../../stores/useThreadStore'
 import { useLiveSessionStore } from '../../stores/useLiveSessionStore'
-import { tauriService } from '../../services/tauriService'
+import { tauriService, WATCHER_EVENTS, type SessionChangedPayload } from '../../services/tauriService'
 import { ThreadList } from '../threads/ThreadList'
 
 export function RightSidebar() {
@@ -26,10 +27,10 @@ export function RightSidebar() {
         const sessions = await tauriService.listSessions(selectedProjectId)
 
         // Convert sessions to threads
-        const threads = sessions.map((sessionId) => ({
-          id: sessionId,
-          session_id: sessionId,
-          name: sessionId,
+        const threads = sessions.map((session) => ({
+          id: session.id,
+          session_id: session.id,
+          name: session.title,
           agents: [],
           message_count: 0,
         }))
@@ -43,6 +44,16 @@ export function RightSidebar() {
     }
 
     loadSessions()
+
+    const unlisteners = [WATCHER_EVENTS.sessionCreated, WATCHER_EVENTS.sessionRemoved].map((event) =>
+      listen<SessionChangedPayload>(event, (e) => {
+        if (e.payload.project_id === selectedProjectId) loadSessions()
+      })
+    )
+
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()))
+    }
   }, [selectedProjectId, setThreads])
 
   const handleLaunchSession = async () => {
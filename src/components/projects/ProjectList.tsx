@@ -12,7 +12,7 @@ interface EditingState {
 }
 
 export function ProjectList({ projects }: ProjectListProps) {
-  const { selectedProjectId, selectProject, toggleFavorite, renameProject } = useProjectStore()
+  const { selectedProjectId, selectProject, toggleFavorite, updateProject } = useProjectStore()
   const [editing, setEditing] = useState<EditingState | null>(null)
   const [contextMenu, setContextMenu] = useState<{ projectId: string; x: number; y: number } | null>(null)
   const inputRef = useRef<HTMLInputElement>(null)
@@ -33,11 +33,11 @@ export function ProjectList({ projects }: ProjectListProps) {
     setContextMenu({ projectId, x: e.clientX, y: e.clientY })
   }
 
-  const handleRenameConfirm = async (oldId: string, newName: string) => {
-    if (newName.trim() && newName !== oldId) {
+  const handleRenameConfirm = async (projectId: string, newName: string) => {
+    const project = projects.find((p) => p.id === projectId)
+    if (project && newName.trim() !== project.name) {
       try {
-        await tauriService.renameProject(oldId, newName)
-        renameProject(oldId, newName)
+        updateProject(await tauriService.renameProject(projectId, newName.trim()))
       } catch (error) {
         console.error('Failed to rename project:', error)
       }
@@ -45,6 +45,15 @@ export function ProjectList({ projects }: ProjectListProps) {
     setEditing(null)
   }
 
+  const handleToggleFavorite = async (project: Project) => {
+    try {
+      await tauriService.updateProjectMetadata(project.id, { favorite: !project.is_favorite })
+      toggleFavorite(project.id)
+    } catch (error) {
+      console.error('Failed to update favorite:', error)
+    }
+  }
+
   const handleKeyDown = (e: React.KeyboardEvent, oldId: string) => {
     if (e.key === 'Enter') {
       handleRenameConfirm(oldId, editing?.newName || '')
@@ -88,7 +97,7 @@ export function ProjectList({ projects }: ProjectListProps) {
             <button
               onClick={(e) => {
                 e.stopPropagation()
-                toggleFavorite(project.id)
+                handleToggleFavorite(project)
               }}
               className="ml-2 text-neural-purple hover:text-neural-cyan transition"
             >
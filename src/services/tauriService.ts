@@ -1,19 +1,378 @@
 import { invoke } from '@tauri-apps/api/core'
 import type { Project } from '../stores/useProjectStore'
+import type { Thread } from '../stores/useThreadStore'
+
+export interface SessionListItem {
+  id: string
+  title: string
+  pinned: boolean
+  tags: string[]
+  in_index: boolean
+  on_disk: boolean
+  size_bytes: number
+  modified_at: string | null
+  message_count: number
+}
+
+// Events emitted by the history watcher when ~/.claude/projects changes
+export const WATCHER_EVENTS = {
+  projectAdded: 'watcher:project-added',
+  projectRemoved: 'watcher:project-removed',
+  sessionCreated: 'watcher:session-created',
+  sessionAppended: 'watcher:session-appended',
+  sessionRemoved: 'watcher:session-removed',
+  sessionMessages: 'watcher:session-messages',
+  health: 'watcher:health',
+} as const
+
+export interface ClaudeRoot {
+  name: string
+  projects_dir: string
+}
+
+// One per Claude root
+export interface WatcherHealth {
+  status: 'starting' | 'waiting_for_root' | 'running' | 'paused' | 'restarting'
+  root: ClaudeRoot
+  since: string
+  restarts: number
+  last_error: string | null
+  last_error_at: string | null
+  // Set on the default root when ~/.ctx/config.json could not be read
+  config_error: string | null
+}
+
+export interface ProjectChangedPayload {
+  project_id: string
+}
+
+export interface SessionChangedPayload {
+  project_id: string
+  session_id: string
+  size: number
+}
+
+// New transcript entries; reset means the file was rewritten and messages
+// holds all of it.
+export interface SessionMessagesPayload {
+  project_id: string
+  session_id: string
+  messages: Session['messages']
+  is_waiting: boolean
+  reset: boolean
+  error: string | null
+}
+
+export interface SessionIndexReport {
+  project_id: string
+  index_exists: boolean
+  index_error: string | null
+  indexed: number
+  on_disk: number
+  missing_files: string[]
+  unindexed: string[]
+  stale: string[]
+}
+
+export interface RelocationFile {
+  path: string
+  rewritten_lines: number
+  total_lines: number
+}
+
+export interface RelocationPlan {
+  project_id: string
+  new_project_id: string
+  source_dir: string
+  target_dir: string
+  old_working_dir: string | null
+  new_working_dir: string
+  files: RelocationFile[]
+  rewritten_lines: number
+  blockers: string[]
+  warnings: string[]
+}
+
+export interface RelocationReport {
+  plan: RelocationPlan
+  dry_run: boolean
+  backup_dir: string | null
+  project: Project | null
+}
+
+// `stream_json` runs the CLI headless and reads typed events instead of raw lines.
+export type SessionMode = 'raw' | 'stream_json'
+
+export type PermissionMode = 'default' | 'acceptEdits' | 'bypassPermissions' | 'plan'
+
+// How a live session is started; every field is optional.
+export interface LaunchSpec {
+  mode?: SessionMode
+  prompt?: string
+  model?: string
+  permission_mode?: PermissionMode
+  allowed_tools?: string[]
+  disallowed_tools?: string[]
+  extra_args?: string[]
+  env?: Record<string, string>
+}
+
+export interface TranscriptLink {
+  session_id: string
+  forked: boolean
+  // The new transcript's id when forked
+  fork_session_id: string | null
+}
+
+export interface StreamResult {
+  subtype: string
+  is_error: boolean
+  result: string | null
+  session_id: string | null
+  total_cost_usd: number | null
+  duration_ms: number | null
+  num_turns: number | null
+  usage: TokenUsage | null
+}
+
+// One event of a stream-json session, as sent on `session-stream-event`.
+export type StreamEvent =
+  | {
+      type: 'init'
+      session_id: string | null
+      model: string | null
+      cwd: string | null
+      tools: string[]
+      permission_mode: string | null
+    }
+  | { type: 'message'; message: Session['messages'][number] }
+  | ({ type: 'result' } & StreamResult)
+  | { type: 'other'; raw: unknown }
+
+export interface StreamSummary {
+  claude_session_id: string | null
+  model: string | null
+  working: boolean
+  tool_calls: ToolCall[]
+  cost_usd: number | null
+  result: StreamResult | null
+}
+
+export interface ProjectMeta {
+  favorite: boolean
+  alias: string | null
+  tags: string[]
+  notes: string | null
+}
+
+export interface SessionMeta {
+  title: string | null
+  pinned: boolean
+  tags: string[]
+  notes: string | null
+}
+
+// Omitted fields are left unchanged; an empty alias, title or note clears it.
+export type ProjectMetaUpdate = Partial<{ favorite: boolean; alias: string; tags: string[]; notes: string }>
+export type SessionMetaUpdate = Partial<{ title: string; pinned: boolean; tags: string[]; notes: string }>
 
 export interface Session {
   id: string
   project_id: string
   name: string
+  summary: string | null
   messages: Array<{
     id: string
     role: string
     content: unknown
-    timestamp: string
+    timestamp: string | null
+    uuid: string | null
+    parent_uuid: string | null
+    session_id: string | null
+    cwd: string | null
+    git_branch: string | null
+    version: string | null
+    is_sidechain: boolean
+    is_meta: boolean
   }>
   created_at: string
   updated_at: string
   is_waiting: boolean
+  cwd: string | null
+  git_branch: string | null
+  version: string | null
+  usage: TokenUsage
+  diagnostics: ParseDiagnostic[]
+}
+
+export interface MessageHeader {
+  id: string
+  role: string
+  timestamp: string | null
+  uuid: string | null
+  parent_uuid: string | null
+  is_sidechain: boolean
+  model: string | null
+  preview: string
+  content_size: number
+  content: unknown | null
+}
+
+export interface SessionPage {
+  session_id: string
+  total: number
+  offset: number
+  messages: MessageHeader[]
+  next_cursor: string | null
+}
+
+export interface ToolCall {
+  id: string
+  name: string
+  input: unknown
+  status: 'pending' | 'success' | 'error'
+  message_id: string
+  result_message_id: string | null
+  started_at: string | null
+  finished_at: string | null
+  duration_ms: number | null
+  result_preview: string | null
+  is_sidechain: boolean
+}
+
+export interface SessionFile {
+  path: string
+  access: 'read' | 'modified'
+  read_count: number
+  modify_count: number
+  tools: string[]
+  first_touched: string | null
+  last_touched: string | null
+}
+
+export interface FileSession {
+  session_id: string
+  path: string
+  access: 'read' | 'modified'
+  last_touched: string | null
+}
+
+export interface FileEdit {
+  tool_call_id: string
+  tool: string
+  path: string
+  timestamp: string | null
+  diff: string
+  exact: boolean
+}
+
+export interface PatchFile {
+  path: string
+  patch_path: string
+  is_new: boolean
+  exact: boolean
+  omitted: boolean
+  edit_count: number
+}
+
+export interface SessionPatch {
+  session_id: string
+  patch: string
+  files: PatchFile[]
+  edits: FileEdit[]
+  warnings: string[]
+}
+
+export interface ShellCommand {
+  tool_call_id: string
+  project_id: string
+  session_id: string
+  command: string
+  description: string | null
+  cwd: string | null
+  status: ToolCall['status']
+  exit_code: number | null
+  timed_out: boolean
+  interrupted: boolean
+  run_in_background: boolean
+  output_preview: string | null
+  // Set when Claude Code recorded the streams separately
+  stdout_preview: string | null
+  stderr_preview: string | null
+  started_at: string | null
+  duration_ms: number | null
+  is_sidechain: boolean
+}
+
+export interface ShellCommandFilter {
+  projectId?: string
+  sessionId?: string
+  since?: string
+  until?: string
+  query?: string
+}
+
+export interface ParseDiagnostic {
+  line: number
+  byte_offset: number
+  kind: 'invalid_json' | 'truncated' | 'invalid_utf8' | 'not_an_object' | 'missing_field'
+  message: string
+  snippet: string
+}
+
+export interface SessionValidation {
+  session_id: string
+  message_count: number
+  diagnostics: ParseDiagnostic[]
+  error: string | null
+}
+
+export interface TailCursor {
+  offset: number
+  carry: number[]
+  message_count: number
+  line_count: number
+  links: Record<string, string | null>
+}
+
+export interface SessionDelta {
+  messages: Session['messages']
+  is_waiting: boolean
+  reset: boolean
+  diagnostics: ParseDiagnostic[]
+  cursor: TailCursor
+}
+
+export interface ConversationTree {
+  session_id: string
+  main: Thread
+  branches: Thread[]
+  sidechains: Thread[]
+}
+
+export interface TokenUsage {
+  input_tokens: number
+  output_tokens: number
+  cache_creation_input_tokens: number
+  cache_read_input_tokens: number
+}
+
+export interface UsageSummary {
+  usage: TokenUsage
+  cost_usd: number
+  by_model: Array<{ model: string; usage: TokenUsage; cost_usd: number | null }>
+  unpriced_models: string[]
+}
+
+export interface SessionUsage {
+  session_id: string
+  summary: UsageSummary
+}
+
+export interface ProjectUsage {
+  project_id: string
+  total: UsageSummary
+  sessions: SessionUsage[]
 }
 
 export const tauriService = {
@@ -25,31 +384,177 @@ export const tauriService = {
     return invoke('get_project', { projectId })
   },
 
-  async listSessions(projectId: string): Promise<string[]> {
+  async listSessions(projectId: string): Promise<SessionListItem[]> {
     return invoke('list_sessions', { projectId })
   },
 
-  async getSession(projectId: string, sessionId: string): Promise<Session> {
-    return invoke('get_session', { projectId, sessionId })
+  async setSessionTitle(projectId: string, sessionId: string, title: string | null): Promise<void> {
+    return invoke('set_session_title', { projectId, sessionId, title })
+  },
+
+  async getSession(projectId: string, sessionId: string, strict = false): Promise<Session> {
+    return invoke('get_session', { projectId, sessionId, strict })
+  },
+
+  async getSessionPage(
+    projectId: string,
+    sessionId: string,
+    options: { offset?: number; limit?: number; after?: string; headersOnly?: boolean } = {},
+  ): Promise<SessionPage> {
+    return invoke('get_session_page', {
+      projectId,
+      sessionId,
+      offset: options.offset ?? null,
+      limit: options.limit ?? null,
+      after: options.after ?? null,
+      headersOnly: options.headersOnly ?? null,
+    })
+  },
+
+  async getMessage(projectId: string, sessionId: string, messageId: string): Promise<Session['messages'][number]> {
+    return invoke('get_message', { projectId, sessionId, messageId })
+  },
+
+  async validateSession(projectId: string, sessionId?: string): Promise<SessionValidation[]> {
+    return invoke('validate_session', { projectId, sessionId: sessionId ?? null })
+  },
+
+  async checkSessionIndex(projectId: string): Promise<SessionIndexReport> {
+    return invoke('check_session_index', { projectId })
+  },
+
+  async getSessionDelta(projectId: string, sessionId: string, cursor?: TailCursor): Promise<SessionDelta> {
+    return invoke('get_session_delta', { projectId, sessionId, cursor: cursor ?? null })
+  },
+
+  async getSessionTree(projectId: string, sessionId: string): Promise<ConversationTree> {
+    return invoke('get_session_tree', { projectId, sessionId })
+  },
+
+  async getToolCalls(projectId: string, sessionId: string): Promise<ToolCall[]> {
+    return invoke('get_tool_calls', { projectId, sessionId })
   },
 
-  async renameProject(oldId: string, newId: string): Promise<void> {
-    return invoke('rename_project', { oldId, newId })
+  async listSessionFiles(projectId: string, sessionId: string): Promise<SessionFile[]> {
+    return invoke('list_session_files', { projectId, sessionId })
   },
 
-  async spawnClaudeSession(projectId: string): Promise<string> {
-    return invoke('spawn_claude_session', { projectId })
+  async listFileSessions(projectId: string, filePath: string): Promise<FileSession[]> {
+    return invoke('list_file_sessions', { projectId, filePath })
+  },
+
+  async exportSessionPatch(
+    projectId: string,
+    sessionId: string,
+    outputPath?: string
+  ): Promise<SessionPatch> {
+    return invoke('export_session_patch', { projectId, sessionId, outputPath: outputPath ?? null })
+  },
+
+  async listShellCommands(filter: ShellCommandFilter = {}): Promise<ShellCommand[]> {
+    return invoke('list_shell_commands', {
+      projectId: filter.projectId ?? null,
+      sessionId: filter.sessionId ?? null,
+      since: filter.since ?? null,
+      until: filter.until ?? null,
+      query: filter.query ?? null,
+    })
+  },
+
+  async getSessionUsage(projectId: string, sessionId: string): Promise<SessionUsage> {
+    return invoke('get_session_usage', { projectId, sessionId })
+  },
+
+  async getProjectUsage(projectId: string): Promise<ProjectUsage> {
+    return invoke('get_project_usage', { projectId })
+  },
+
+  async getProjectMetadata(projectId: string): Promise<ProjectMeta> {
+    return invoke('get_project_metadata', { projectId })
+  },
+
+  async updateProjectMetadata(projectId: string, update: ProjectMetaUpdate): Promise<ProjectMeta> {
+    return invoke('update_project_metadata', { projectId, update })
+  },
+
+  async getSessionMetadata(projectId: string, sessionId: string): Promise<SessionMeta> {
+    return invoke('get_session_metadata', { projectId, sessionId })
+  },
+
+  async listSessionMetadata(projectId: string): Promise<Record<string, SessionMeta>> {
+    return invoke('list_session_metadata', { projectId })
+  },
+
+  async updateSessionMetadata(
+    projectId: string,
+    sessionId: string,
+    update: SessionMetaUpdate
+  ): Promise<SessionMeta> {
+    return invoke('update_session_metadata', { projectId, sessionId, update })
+  },
+
+  // Sets a display alias; the project id and Claude's files are unchanged.
+  async renameProject(projectId: string, name: string): Promise<Project> {
+    return invoke('rename_project', { projectId, name })
+  },
+
+  // Moves the project's Claude history to follow a moved repository, changing
+  // the project id. Run with dryRun first to review the plan; a real run fails
+  // unless confirm is true.
+  async relocateProject(
+    projectId: string,
+    newWorkingDir: string,
+    options: { dryRun: boolean; confirm?: boolean }
+  ): Promise<RelocationReport> {
+    return invoke('relocate_project', {
+      projectId,
+      newWorkingDir,
+      dryRun: options.dryRun,
+      confirm: options.confirm ?? false,
+    })
+  },
+
+  async getWatcherHealth(): Promise<WatcherHealth[]> {
+    return invoke('get_watcher_health')
+  },
+
+  // Changes made while paused are reported on resume.
+  async pauseWatcher(): Promise<void> {
+    return invoke('pause_watcher')
+  },
+
+  async resumeWatcher(): Promise<void> {
+    return invoke('resume_watcher')
+  },
+
+  // Runs Claude Code in the project's working directory.
+  async spawnClaudeSession(projectId: string, spec?: LaunchSpec): Promise<string> {
+    return invoke('spawn_claude_session', { projectId, spec: spec ?? null })
+  },
+
+  // Continues a past session's transcript live, or branches it with fork.
+  async resumeClaudeSession(
+    projectId: string,
+    sessionId: string,
+    options: { fork?: boolean; spec?: LaunchSpec } = {}
+  ): Promise<string> {
+    return invoke('resume_claude_session', {
+      projectId,
+      sessionId,
+      fork: options.fork ?? false,
+      spec: options.spec ?? null,
+    })
   },
 
   async terminateSession(sessionId: string): Promise<void> {
     return invoke('terminate_session', { sessionId })
   },
 
-  async listActiveSessions(): Promise<Array<{ id: string; projectId: string; state: string; createdAt: string; outputCount: number }>> {
+  async listActiveSessions(): Promise<Array<{ id: string; projectId: string; state: string; createdAt: string; outputCount: number; resumed_from: TranscriptLink | null; mode: SessionMode }>> {
     return invoke('list_active_sessions')
   },
 
-  async getActiveSession(sessionId: string): Promise<{ id: string; projectId: string; state: string; createdAt: string; outputCount: number }> {
+  async getActiveSession(sessionId: string): Promise<{ id: string; projectId: string; state: string; createdAt: string; outputCount: number; resumed_from: TranscriptLink | null; mode: SessionMode }> {
     return invoke('get_session', { sessionId })
   },
 
@@ -60,4 +565,8 @@ export const tauriService = {
   async sendInputToSession(sessionId: string, input: string): Promise<void> {
     return invoke('send_input_to_session', { sessionId, input })
   },
+
+  async getSessionStream(sessionId: string): Promise<StreamSummary> {
+    return invoke('get_session_stream', { sessionId })
+  },
 }
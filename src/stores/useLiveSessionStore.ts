@@ -1,5 +1,6 @@
 import { create } from 'zustand'
 import { listen } from '@tauri-apps/api/event'
+import type { SessionMode, StreamResult, TranscriptLink } from '../services/tauriService'
 
 export interface OutputLine {
   timestamp: string
@@ -13,11 +14,19 @@ export interface LiveSession {
   state: 'idle' | 'working' | 'waiting'
   output: OutputLine[]
   createdAt: string
+  // The past session this one continues, linking it to the history view
+  resumedFrom: TranscriptLink | null
+  mode: SessionMode
+  // Reported by stream-json sessions at the end of each turn
+  costUsd: number | null
+  lastResult: StreamResult | null
 }
 
 interface SessionCreatedPayload {
   session_id: string
   project_id: string
+  resumed_from: TranscriptLink | null
+  mode: SessionMode
 }
 
 interface SessionTerminatedPayload {
@@ -36,6 +45,12 @@ interface SessionOutputPayload {
   type: 'stdout' | 'stderr'
 }
 
+interface SessionResultPayload {
+  session_id: string
+  result: StreamResult
+  cost_usd: number | null
+}
+
 interface SessionCompletedPayload {
   session_id: string
   exit_code: number | null
@@ -56,13 +71,17 @@ interface LiveSessionStore {
 export const useLiveSessionStore = create<LiveSessionStore>((set, get) => {
   // Set up event listeners for Tauri events
   listen<SessionCreatedPayload>('session-created', (event) => {
-    const { session_id, project_id } = event.payload
+    const { session_id, project_id, resumed_from, mode } = event.payload
     const newSession: LiveSession = {
       id: session_id,
       projectId: project_id,
       state: 'idle',
       output: [],
       createdAt: new Date().toISOString(),
+      resumedFrom: resumed_from,
+      mode,
+      costUsd: null,
+      lastResult: null,
     }
     set((storeState) => ({
       sessions: [...storeState.sessions, newSession],
@@ -107,6 +126,15 @@ export const useLiveSessionStore = create<LiveSessionStore>((set, get) => {
     }))
   })
 
+  listen<SessionResultPayload>('session-result', (event) => {
+    const { session_id, result, cost_usd } = event.payload
+    set((storeState) => ({
+      sessions: storeState.sessions.map((s) =>
+        s.id === session_id ? { ...s, costUsd: cost_usd, lastResult: result } : s
+      ),
+    }))
+  })
+
   listen<SessionCompletedPayload>('session-completed', (event) => {
     const { session_id, exit_code } = event.payload
     set((storeState) => ({
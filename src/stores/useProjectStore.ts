@@ -4,10 +4,17 @@ import { persist } from 'zustand/middleware'
 export interface Project {
   id: string
   name: string
+  // Claude root the project lives in; 'default' unless extra roots are configured
+  root: string
   path: string
+  working_dir: string | null
   sessions: string[]
   created_at: string
+  updated_at: string
   is_favorite: boolean
+  alias: string | null
+  tags: string[]
+  notes: string | null
 }
 
 interface ProjectStore {
@@ -17,7 +24,7 @@ interface ProjectStore {
   addProject: (project: Project) => void
   selectProject: (projectId: string) => void
   toggleFavorite: (projectId: string) => void
-  renameProject: (oldId: string, newId: string) => void
+  updateProject: (project: Project) => void
 }
 
 export const useProjectStore = create<ProjectStore>()(
@@ -35,11 +42,8 @@ export const useProjectStore = create<ProjectStore>()(
           p.id === projectId ? { ...p, is_favorite: !p.is_favorite } : p
         ),
       })),
-      renameProject: (oldId, newId) => set((state) => ({
-        projects: state.projects.map((p) =>
-          p.id === oldId ? { ...p, id: newId, name: newId } : p
-        ),
-        selectedProjectId: state.selectedProjectId === oldId ? newId : state.selectedProjectId,
+      updateProject: (project) => set((state) => ({
+        projects: state.projects.map((p) => (p.id === project.id ? project : p)),
       })),
     }),
     {
@@ -6,6 +6,9 @@ export interface Thread {
   name: string
   agents: AgentState[]
   message_count: number
+  kind?: 'main' | 'branch' | 'sidechain'
+  message_ids?: string[]
+  fork_point?: string | null
 }
 
 export interface AgentState {